@@ -3,10 +3,11 @@
 
 use clap::Parser;
 use kompari::DirDiffConfig;
-use kompari_tasks::{Actions, Args, Task};
+use kompari_tasks::{Actions, Args, ERROR_EXIT_CODE, Task};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, ExitCode};
 
+#[derive(Debug)]
 struct ActionsImpl();
 
 impl Actions for ActionsImpl {
@@ -20,7 +21,7 @@ impl Actions for ActionsImpl {
     }
 }
 
-fn main() -> kompari::Result<()> {
+fn main() -> ExitCode {
     let tests_path = Path::new(env!("CARGO_MANIFEST_DIR"))
         .parent()
         .unwrap()
@@ -33,6 +34,11 @@ fn main() -> kompari::Result<()> {
     let diff_config = DirDiffConfig::new(snapshots_path, current_path);
     let actions = ActionsImpl();
     let mut task = Task::new(diff_config, Box::new(actions));
-    task.run(&args)?;
-    Ok(())
+    match task.run(&args) {
+        Ok(outcome) => ExitCode::from(outcome.exit_code()),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::from(ERROR_EXIT_CODE)
+        }
+    }
 }
@@ -1,8 +1,9 @@
 // Copyright 2025 the Kompari Authors
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+use crate::imageutils::load_image_with;
 use crate::imgdiff::{ImageDifference, compare_images};
-use crate::{list_image_dir_names, load_image};
+use crate::pairing::{NameMapping, Pair, PairingRules, pairs_from_paths};
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
 use std::path::{Path, PathBuf};
@@ -13,7 +14,13 @@ pub struct DirDiffConfig {
     right_path: PathBuf,
     ignore_left_missing: bool,
     ignore_right_missing: bool,
-    filter_name: Option<String>,
+    include_matches: bool,
+    resolve_lfs: bool,
+    pairing_rules: PairingRules,
+    #[cfg(feature = "manifest")]
+    manifest: Option<crate::Manifest>,
+    #[cfg(feature = "git")]
+    left_revision: Option<std::sync::Arc<crate::GitRevisionDir>>,
 
     /// The per-pixel distance that we tolerate. Only if the distance is greater than this will the
     /// pixel be counted as being different.
@@ -30,7 +37,13 @@ impl DirDiffConfig {
             right_path,
             ignore_left_missing: false,
             ignore_right_missing: false,
-            filter_name: None,
+            include_matches: false,
+            resolve_lfs: false,
+            pairing_rules: PairingRules::default(),
+            #[cfg(feature = "manifest")]
+            manifest: None,
+            #[cfg(feature = "git")]
+            left_revision: None,
             pixel_distance_tolerance: 0,
         }
     }
@@ -43,41 +56,104 @@ impl DirDiffConfig {
         &self.right_path
     }
 
-    pub fn create_diff(&self) -> crate::Result<DirDiff> {
-        let pairs = pairs_from_paths(
-            &self.left_path,
+    /// Journal of bless operations of left images, stored next to the left directory.
+    ///
+    /// When pairs are taken from a manifest without a left directory, the journal is stored
+    /// next to the manifest file; a manifest that was not loaded from a file has no journal.
+    pub fn journal(&self) -> crate::Result<crate::BlessJournal> {
+        if !self.left_path.as_os_str().is_empty() {
+            return Ok(crate::BlessJournal::for_target_dir(&self.left_path));
+        }
+        #[cfg(feature = "manifest")]
+        if let Some(path) = self.manifest.as_ref().and_then(|manifest| manifest.path()) {
+            return Ok(crate::BlessJournal::for_target_dir(path));
+        }
+        Err(crate::Error::GenericError(
+            "No left directory or manifest file to store the bless journal next to".into(),
+        ))
+    }
+
+    /// Lists pairs of images that would be compared by [`Self::create_diff`].
+    pub fn pairs(&self) -> crate::Result<Vec<Pair>> {
+        #[cfg(feature = "manifest")]
+        if let Some(manifest) = &self.manifest {
+            let mut pairs = manifest.pairs().to_vec();
+            self.pairing_rules.retain_matching(&mut pairs)?;
+            return Ok(pairs);
+        }
+        pairs_from_paths(
+            self.left_source_path(),
             &self.right_path,
-            self.filter_name.as_deref(),
-        )?;
-        let tolerance = self.pixel_distance_tolerance;
+            &self.pairing_rules,
+        )
+    }
+
+    /// Directory from which left images are read; it differs from
+    /// [`Self::left_path`] if a left revision is set.
+    pub fn left_source_path(&self) -> &Path {
+        #[cfg(feature = "git")]
+        if let Some(revision_dir) = &self.left_revision {
+            return revision_dir.path();
+        }
+        &self.left_path
+    }
+
+    pub fn create_diff(&self) -> crate::Result<DirDiff> {
+        let pairs = self.pairs()?;
         let diffs: Vec<_> = pairs
             .into_par_iter()
-            .filter_map(|pair| {
-                let image_diff = compute_pair_diff(&pair, tolerance);
-                if matches!(image_diff, Ok(ImageDifference::None)) {
-                    return None;
-                }
-                if self.ignore_left_missing
-                    && matches!(image_diff, Err(ref e) if e.is_left_missing())
-                {
-                    return None;
-                }
-                if self.ignore_right_missing
-                    && matches!(image_diff, Err(ref e) if e.is_right_missing())
-                {
-                    return None;
-                }
-                Some(PairResult {
-                    title: pair.title,
-                    left: pair.left,
-                    right: pair.right,
-                    image_diff,
-                })
-            })
+            .filter_map(|pair| self.compare_pair(pair))
             .collect();
         Ok(DirDiff { diffs })
     }
 
+    /// Compare a single pair from [`Self::pairs`].
+    ///
+    /// Returns `None` if the result would not be included in the diff.
+    pub fn compare_pair(&self, pair: Pair) -> Option<PairResult> {
+        let image_diff = compute_pair_diff(
+            &pair,
+            pair.pixel_distance_tolerance
+                .unwrap_or(self.pixel_distance_tolerance),
+            self.resolve_lfs,
+        );
+        self.is_reported(&image_diff).then(|| PairResult {
+            title: pair.title,
+            left: pair.left,
+            right: pair.right,
+            image_diff,
+        })
+    }
+
+    /// Returns `false` for results that are not included in the diff (matches and ignored
+    /// missing files).
+    pub(crate) fn is_reported(&self, image_diff: &Result<ImageDifference, LeftRightError>) -> bool {
+        match image_diff {
+            Ok(ImageDifference::None) => self.include_matches,
+            Err(e) if self.ignore_left_missing && e.is_left_missing() => false,
+            Err(e) if self.ignore_right_missing && e.is_right_missing() => false,
+            _ => true,
+        }
+    }
+
+    pub(crate) fn pairing_rules(&self) -> &PairingRules {
+        &self.pairing_rules
+    }
+
+    pub(crate) fn pixel_distance_tolerance(&self) -> u8 {
+        self.pixel_distance_tolerance
+    }
+
+    /// Returns `true` if Git LFS pointers are resolved from the local LFS storage.
+    pub fn resolve_lfs(&self) -> bool {
+        self.resolve_lfs
+    }
+
+    /// Keep pairs of matching images in the diff, e.g. to create a full gallery of checked images.
+    pub fn set_include_matches(&mut self, value: bool) {
+        self.include_matches = value;
+    }
+
     pub fn set_ignore_left_missing(&mut self, value: bool) {
         self.ignore_left_missing = value;
     }
@@ -86,16 +162,88 @@ impl DirDiffConfig {
         self.ignore_right_missing = value;
     }
 
+    /// If enabled, Git LFS pointer files are resolved from the local LFS storage,
+    /// see [`load_image_resolve_lfs`](crate::load_image_resolve_lfs).
+    #[cfg(feature = "lfs")]
+    pub fn set_resolve_lfs(&mut self, value: bool) {
+        self.resolve_lfs = value;
+    }
+
+    /// Keep only pairs whose title contains the given string.
     pub fn set_filter_name(&mut self, value: Option<String>) {
-        self.filter_name = value;
+        self.pairing_rules.filter_name = value;
+    }
+
+    /// Set how names of files in the left directory are transformed before pairing.
+    pub fn set_left_name_mapping(&mut self, value: NameMapping) {
+        self.pairing_rules.left_mapping = value;
+    }
+
+    /// Set how names of files in the right directory are transformed before pairing.
+    pub fn set_right_name_mapping(&mut self, value: NameMapping) {
+        self.pairing_rules.right_mapping = value;
+    }
+
+    /// If enabled, subdirectories are ignored and files are paired only by their file names.
+    pub fn set_flatten(&mut self, value: bool) {
+        self.pairing_rules.flatten = value;
+    }
+
+    /// If enabled, file names are paired (and globs are matched) ignoring case.
+    pub fn set_case_insensitive(&mut self, value: bool) {
+        self.pairing_rules.case_insensitive = value;
+    }
+
+    /// Keep only pairs whose title matches at least one of the given globs.
+    /// An empty list keeps all pairs.
+    pub fn set_include_globs(&mut self, value: Vec<String>) {
+        self.pairing_rules.include_globs = value;
+    }
+
+    /// Remove pairs whose title matches any of the given globs.
+    pub fn set_exclude_globs(&mut self, value: Vec<String>) {
+        self.pairing_rules.exclude_globs = value;
     }
 
     pub fn set_pixel_distance_tolerance(&mut self, value: u8) {
         self.pixel_distance_tolerance = value;
     }
+
+    /// Read left images from the given git revision (e.g. `HEAD`) of the repository
+    /// containing the left path, instead of from the working tree.
+    ///
+    /// The images are exported immediately into a temporary directory that lives as long as
+    /// this configuration (or any of its clones). Images written into it would not reach
+    /// the repository, so blessing is not possible in this mode.
+    #[cfg(feature = "git")]
+    pub fn set_left_revision(&mut self, revision: Option<&str>) -> crate::Result<()> {
+        self.left_revision = revision
+            .map(|revision| crate::GitRevisionDir::export(&self.left_path, revision))
+            .transpose()?
+            .map(std::sync::Arc::new);
+        Ok(())
+    }
+
+    /// Git revision from which left images are read; always `None` without the `git` feature.
+    pub fn left_revision(&self) -> Option<&str> {
+        #[cfg(feature = "git")]
+        if let Some(revision_dir) = &self.left_revision {
+            return Some(revision_dir.revision());
+        }
+        None
+    }
+
+    /// Take pairs from the manifest instead of pairing files from the left and right directories.
+    ///
+    /// Name mappings are not applied to manifest entries, but the name filter and globs are.
+    #[cfg(feature = "manifest")]
+    pub fn set_manifest(&mut self, value: Option<crate::Manifest>) {
+        self.manifest = value;
+    }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum LeftRightError {
     Left(crate::Error),
     Right(crate::Error),
@@ -142,6 +290,7 @@ impl LeftRightError {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PairResult {
     pub title: String,
     pub left: PathBuf,
@@ -150,6 +299,7 @@ pub struct PairResult {
 }
 
 #[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DirDiff {
     diffs: Vec<PairResult>,
 }
@@ -160,53 +310,13 @@ impl DirDiff {
     }
 }
 
-pub(crate) struct Pair {
-    pub title: String,
-    pub left: PathBuf,
-    pub right: PathBuf,
-}
-
-pub(crate) fn pairs_from_paths(
-    left_path: &Path,
-    right_path: &Path,
-    filter_name: Option<&str>,
-) -> crate::Result<Vec<Pair>> {
-    if !left_path.is_dir() {
-        return Err(crate::Error::NotDirectory(left_path.to_path_buf()));
-    }
-    if !right_path.is_dir() {
-        return Err(crate::Error::NotDirectory(right_path.to_path_buf()));
-    }
-    let mut names: Vec<_> = list_image_dir_names(left_path)?.collect();
-    names.extend(list_image_dir_names(right_path)?);
-    names.sort_unstable();
-    names.dedup();
-    names.retain(|filename| {
-        filter_name
-            .as_ref()
-            .map(|f| filename.to_string_lossy().contains(f))
-            .unwrap_or(true)
-    });
-    Ok(names
-        .into_iter()
-        .map(|name| {
-            let left = left_path.join(&name);
-            let right = right_path.join(&name);
-            Pair {
-                title: name.to_string_lossy().to_string(),
-                left,
-                right,
-            }
-        })
-        .collect())
-}
-
-fn compute_pair_diff(
+pub(crate) fn compute_pair_diff(
     pair: &Pair,
     pixel_distance_tolerance: u8,
+    resolve_lfs: bool,
 ) -> Result<ImageDifference, LeftRightError> {
-    let left = load_image(&pair.left);
-    let right = load_image(&pair.right);
+    let left = load_image_with(&pair.left, resolve_lfs);
+    let right = load_image_with(&pair.right, resolve_lfs);
     let (left_image, right_image) = match (left, right) {
         (Ok(left_image), Ok(right_image)) => (left_image, right_image),
         (Err(e), Ok(_)) => return Err(LeftRightError::Left(e)),
@@ -25,6 +25,17 @@ pub fn list_image_dir(dir_path: &Path) -> Result<impl Iterator<Item = PathBuf>,
     }))
 }
 
+/// Create a directory (and its parents) for local state with a `.gitignore`
+/// that ignores all of its content, so the state is not committed by accident.
+pub fn create_ignored_dir(path: &Path) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(path)?;
+    let gitignore = path.join(".gitignore");
+    if !gitignore.exists() {
+        std::fs::write(gitignore, "# Local state of Kompari\n*\n")?;
+    }
+    Ok(())
+}
+
 pub fn list_image_dir_names(
     dir_path: &Path,
 ) -> Result<impl Iterator<Item = PathBuf> + '_, std::io::Error> {
@@ -0,0 +1,100 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::path::{Path, PathBuf};
+
+/// Images of a directory as stored in a git revision.
+///
+/// The images are read directly from the object database of the repository containing the
+/// directory (no checkout is needed) and are written into a temporary directory that is
+/// removed when this value is dropped.
+#[derive(Debug)]
+pub struct GitRevisionDir {
+    revision: String,
+    dir: tempfile::TempDir,
+}
+
+fn git_error(error: impl std::error::Error + Send + Sync + 'static) -> crate::Error {
+    crate::Error::GenericError(Box::new(error))
+}
+
+fn message_error(message: String) -> crate::Error {
+    crate::Error::GenericError(message.into())
+}
+
+impl GitRevisionDir {
+    /// Export images under `path` as they are in `revision` (e.g. `HEAD`, a branch name or
+    /// a commit hash).
+    pub fn export(path: &Path, revision: &str) -> crate::Result<Self> {
+        if !path.is_dir() {
+            return Err(crate::Error::NotDirectory(path.to_path_buf()));
+        }
+        let path = path.canonicalize()?;
+        let repo = gix::discover(&path).map_err(git_error)?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| message_error("Repository has no working tree".into()))?
+            .canonicalize()?;
+        let relative_path = path
+            .strip_prefix(&workdir)
+            .map_err(|_| crate::Error::NotDirectory(path.clone()))?;
+        let root = repo
+            .rev_parse_single(revision)
+            .map_err(git_error)?
+            .object()
+            .map_err(git_error)?
+            .peel_to_tree()
+            .map_err(git_error)?;
+        let tree = if relative_path.as_os_str().is_empty() {
+            root
+        } else {
+            let entry = root
+                .lookup_entry_by_path(relative_path)
+                .map_err(git_error)?
+                .filter(|entry| entry.mode().is_tree())
+                .ok_or_else(|| {
+                    message_error(format!(
+                        "Directory `{}` does not exist in revision `{revision}`",
+                        relative_path.display()
+                    ))
+                })?;
+            entry.object().map_err(git_error)?.into_tree()
+        };
+        let dir = tempfile::Builder::new().prefix("kompari-git-").tempdir()?;
+        export_tree(&tree, dir.path())?;
+        Ok(Self {
+            revision: revision.to_string(),
+            dir,
+        })
+    }
+
+    pub fn revision(&self) -> &str {
+        &self.revision
+    }
+
+    /// Temporary directory containing the exported images.
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+fn export_tree(tree: &gix::Tree<'_>, target: &Path) -> crate::Result<()> {
+    for entry in tree.iter() {
+        let entry = entry.map_err(git_error)?;
+        let name = entry.filename().to_string();
+        let mode = entry.mode();
+        if mode.is_tree() {
+            let subdir: PathBuf = target.join(&name);
+            std::fs::create_dir(&subdir)?;
+            export_tree(&entry.object().map_err(git_error)?.into_tree(), &subdir)?;
+        } else if mode.is_blob()
+            && Path::new(&name)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+        {
+            let object = entry.object().map_err(git_error)?;
+            std::fs::write(target.join(&name), &object.data)?;
+        }
+    }
+    Ok(())
+}
@@ -23,6 +23,45 @@ pub fn load_image(path: &Path) -> crate::Result<MinImage> {
     MinImage::decode_from_png(Cursor::new(bytes))
 }
 
+/// Load an image like [`load_image`], but if the file is a Git LFS pointer whose object is
+/// present in the local LFS storage of the repository (e.g. when the smudge step was skipped),
+/// the image is loaded from there.
+///
+/// See [`read_png_resolve_lfs`] for details.
+#[cfg(feature = "lfs")]
+pub fn load_image_resolve_lfs(path: &Path) -> crate::Result<MinImage> {
+    log::debug!("Loading image {}", path.display());
+    MinImage::decode_from_png(Cursor::new(read_png_resolve_lfs(path)?))
+}
+
+/// Read the content of an image file; if the file is a Git LFS pointer, the content of the
+/// object from the local LFS storage is returned instead.
+///
+/// The object is checked against the SHA-256 and size from the pointer.
+/// [`Error::LFSMissing`](crate::Error::LFSMissing) is returned if the object is not
+/// available locally.
+#[cfg(feature = "lfs")]
+pub fn read_png_resolve_lfs(path: &Path) -> crate::Result<Vec<u8>> {
+    if !path.is_file() {
+        return Err(crate::Error::FileNotFound(path.to_path_buf()));
+    }
+    let bytes = fs::read(path)?;
+    match crate::lfs::parse_lfs_pointer(&bytes) {
+        Some(pointer) => crate::lfs::load_lfs_object(path, &pointer),
+        None => Ok(bytes),
+    }
+}
+
+pub(crate) fn load_image_with(path: &Path, resolve_lfs: bool) -> crate::Result<MinImage> {
+    #[cfg(feature = "lfs")]
+    if resolve_lfs {
+        return load_image_resolve_lfs(path);
+    }
+    #[cfg(not(feature = "lfs"))]
+    let _ = resolve_lfs;
+    load_image(path)
+}
+
 #[cfg(feature = "oxipng")]
 pub fn optimize_png(data: Vec<u8>, opt_level: SizeOptimizationLevel) -> Vec<u8> {
     let preset = match opt_level {
@@ -48,16 +87,52 @@ pub fn image_to_png(image: &MinImage, opt_level: SizeOptimizationLevel) -> Vec<u
     optimize_png(data, opt_level)
 }
 
+fn same_pixels(a: &MinImage, b: &MinImage) -> bool {
+    a.width == b.width && a.height == b.height && a.data == b.data
+}
+
+/// Returns `true` if `target` exists and has the same pixels as `image`.
+fn is_unchanged(image: &MinImage, target: &Path) -> bool {
+    target.is_file() && load_image(target).is_ok_and(|current| same_pixels(image, &current))
+}
+
+/// Copy the image `source` to `target`, re-encoded with the highest size optimization.
+///
+/// The target is left untouched if it already has the same pixels, so accepting an
+/// unchanged image does not create a binary diff. The optimized data are verified
+/// to decode to the source pixels before they are written.
+///
+/// Returns `true` if the target was written.
 #[cfg(feature = "oxipng")]
-pub fn bless_image(source: &Path, target: &Path) -> crate::Result<()> {
+pub fn bless_image(source: &Path, target: &Path) -> crate::Result<bool> {
     let image = load_image(source)?;
-    let data = image_to_png(&image, SizeOptimizationLevel::High);
+    if is_unchanged(&image, target) {
+        return Ok(false);
+    }
+    let mut data = image_to_png(&image, SizeOptimizationLevel::High);
+    let decoded = MinImage::decode_from_png(Cursor::new(&data))?;
+    if !same_pixels(&image, &decoded) {
+        log::warn!(
+            "Optimized image {} does not match the source, storing it without optimization",
+            source.display()
+        );
+        data = image_to_png(&image, SizeOptimizationLevel::None);
+    }
     fs::write(target, data)?;
-    Ok(())
+    Ok(true)
 }
 
+/// Copy the image `source` to `target`.
+///
+/// The target is left untouched if it already has the same pixels, so accepting an
+/// unchanged image does not create a binary diff.
+///
+/// Returns `true` if the target was written.
 #[cfg(not(feature = "oxipng"))]
-pub fn bless_image(source: &Path, target: &Path) -> crate::Result<()> {
+pub fn bless_image(source: &Path, target: &Path) -> crate::Result<bool> {
+    if is_unchanged(&load_image(source)?, target) {
+        return Ok(false);
+    }
     fs::copy(source, target)?;
-    Ok(())
+    Ok(true)
 }
@@ -32,6 +32,11 @@ pub struct DiffImage {
     pub image: MinImage,
 }
 
+/// Result of comparing two images.
+///
+/// With the `serde` feature, the difference is serializable; diff images are skipped.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum ImageDifference {
     None,
     SizeMismatch {
@@ -39,7 +44,9 @@ pub enum ImageDifference {
         right_size: (u32, u32),
     },
     Content {
+        #[cfg_attr(feature = "serde", serde(skip))]
         diff_images: Vec<DiffImage>,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_background"))]
         background: Option<Rgba8>,
         // If the background is not detected, then the following values are related
         // to the whole image. Otherwise, they are related only to pixels that are
@@ -50,6 +57,21 @@ pub enum ImageDifference {
     },
 }
 
+#[cfg(feature = "serde")]
+#[allow(
+    clippy::trivially_copy_pass_by_ref,
+    reason = "Signature required by serde"
+)]
+fn serialize_background<S: serde::Serializer>(
+    background: &Option<Rgba8>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+    background
+        .map(|c| [c.r, c.g, c.b, c.a])
+        .serialize(serializer)
+}
+
 impl Debug for ImageDifference {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -0,0 +1,183 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::{bless_image, create_ignored_dir};
+use std::path::{Path, PathBuf};
+
+const ENTRY_FILE: &str = "entry";
+const DATA_FILE: &str = "data";
+const DEFAULT_MAX_ENTRIES: usize = 20;
+
+/// Bless operation removed from the journal by [`BlessJournal::undo`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoneBless {
+    /// Restored paths.
+    pub paths: Vec<PathBuf>,
+    /// Data stored by [`BlessJournal::bless_with_data`].
+    pub data: Option<String>,
+}
+
+/// Journal of bless operations that keeps previous content of blessed images, so they can
+/// be restored by [`BlessJournal::undo`].
+///
+/// Each call of [`BlessJournal::bless`] creates one entry in the journal directory;
+/// only the newest entries are kept (20 by default). The directory contains a `.gitignore`,
+/// so backups are not committed.
+#[derive(Debug, Clone)]
+pub struct BlessJournal {
+    dir: PathBuf,
+    max_entries: usize,
+}
+
+impl BlessJournal {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+
+    /// Journal stored next to the directory with blessed images,
+    /// e.g. `tests/.snapshots.kompari-journal` for `tests/snapshots`,
+    /// or next to the manifest file listing them.
+    ///
+    /// See also [`crate::DirDiffConfig::journal`].
+    pub fn for_target_dir(target_dir: &Path) -> Self {
+        let dir = match (target_dir.parent(), target_dir.file_name()) {
+            (Some(parent), Some(name)) => {
+                parent.join(format!(".{}.kompari-journal", name.to_string_lossy()))
+            }
+            _ => PathBuf::from(".kompari-journal"),
+        };
+        Self::new(dir)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Maximal number of operations that can be undone; older entries are removed.
+    pub fn set_max_entries(&mut self, value: usize) {
+        self.max_entries = value.max(1);
+    }
+
+    fn entry_ids(&self) -> crate::Result<Vec<u32>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if let Some(id) = entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+                if entry.path().join(ENTRY_FILE).is_file() {
+                    ids.push(id);
+                }
+            }
+        }
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    /// Number of operations that can be undone.
+    pub fn len(&self) -> crate::Result<usize> {
+        Ok(self.entry_ids()?.len())
+    }
+
+    pub fn is_empty(&self) -> crate::Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Bless images (copy each source over its target) as one undoable operation.
+    ///
+    /// Returns the number of written targets; targets with unchanged pixels are not written.
+    pub fn bless(&self, images: &[(&Path, &Path)]) -> crate::Result<usize> {
+        self.bless_with_data(images, None)
+    }
+
+    /// Like [`Self::bless`], and store `data` in the entry, e.g. state of a tool that
+    /// should be restored together with the images.
+    pub fn bless_with_data(
+        &self,
+        images: &[(&Path, &Path)],
+        data: Option<&str>,
+    ) -> crate::Result<usize> {
+        let ids = self.entry_ids()?;
+        let id = ids.last().map(|id| id + 1).unwrap_or(1);
+        // Keep room for the new entry
+        for old_id in &ids[..(ids.len() + 1).saturating_sub(self.max_entries)] {
+            std::fs::remove_dir_all(self.dir.join(format!("{old_id:06}")))?;
+        }
+        create_ignored_dir(&self.dir)?;
+        let entry_dir = self.dir.join(format!("{id:06}"));
+        std::fs::create_dir_all(&entry_dir)?;
+        let mut lines = String::new();
+        for (idx, (_, target)) in images.iter().enumerate() {
+            let target = std::path::absolute(target)?;
+            if target.is_file() {
+                std::fs::copy(&target, entry_dir.join(format!("{idx}.png")))?;
+                lines.push_str(&format!("replaced {idx} {}\n", target.display()));
+            } else {
+                lines.push_str(&format!("created {idx} {}\n", target.display()));
+            }
+        }
+        if let Some(data) = data {
+            std::fs::write(entry_dir.join(DATA_FILE), data)?;
+        }
+        // The entry file is written last, so an interrupted backup is not a valid entry
+        std::fs::write(entry_dir.join(ENTRY_FILE), lines)?;
+        let mut n_written = 0;
+        for (source, target) in images {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            if bless_image(source, target)? {
+                n_written += 1;
+            }
+        }
+        Ok(n_written)
+    }
+
+    /// Restore images of the last bless operation and remove it from the journal.
+    ///
+    /// Returns `None` if there is nothing to undo.
+    pub fn undo(&self) -> crate::Result<Option<UndoneBless>> {
+        let Some(id) = self.entry_ids()?.last().copied() else {
+            return Ok(None);
+        };
+        let entry_dir = self.dir.join(format!("{id:06}"));
+        let content = std::fs::read_to_string(entry_dir.join(ENTRY_FILE))?;
+        let mut restored = Vec::new();
+        for line in content.lines() {
+            let mut parts = line.splitn(3, ' ');
+            let (Some(kind), Some(idx), Some(path)) = (parts.next(), parts.next(), parts.next())
+            else {
+                return Err(crate::Error::GenericError(
+                    format!("Invalid journal entry `{}`", entry_dir.display()).into(),
+                ));
+            };
+            let path = PathBuf::from(path);
+            match kind {
+                "replaced" => {
+                    std::fs::copy(entry_dir.join(format!("{idx}.png")), &path)?;
+                }
+                _ => {
+                    if path.is_file() {
+                        std::fs::remove_file(&path)?;
+                    }
+                }
+            }
+            restored.push(path);
+        }
+        let data_path = entry_dir.join(DATA_FILE);
+        let data = if data_path.is_file() {
+            Some(std::fs::read_to_string(data_path)?)
+        } else {
+            None
+        };
+        std::fs::remove_dir_all(&entry_dir)?;
+        Ok(Some(UndoneBless {
+            paths: restored,
+            data,
+        }))
+    }
+}
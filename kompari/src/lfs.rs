@@ -0,0 +1,91 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Content of a Git LFS pointer file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LfsPointer {
+    pub oid: String,
+    pub size: u64,
+}
+
+const LFS_VERSION_LINE: &str = "version https://git-lfs.github.com/spec/v1";
+
+/// Parse a pointer file, see <https://github.com/git-lfs/git-lfs/blob/main/docs/spec.md>.
+pub(crate) fn parse_lfs_pointer(data: &[u8]) -> Option<LfsPointer> {
+    let text = std::str::from_utf8(data).ok()?;
+    let mut lines = text.lines();
+    if lines.next()? != LFS_VERSION_LINE {
+        return None;
+    }
+    let mut oid = None;
+    let mut size = None;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("oid sha256:") {
+            if value.len() != 64 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            oid = Some(value.to_ascii_lowercase());
+        } else if let Some(value) = line.strip_prefix("size ") {
+            size = Some(value.parse().ok()?);
+        }
+    }
+    Some(LfsPointer {
+        oid: oid?,
+        size: size?,
+    })
+}
+
+/// Find the git directory that stores LFS objects for a file in a working tree.
+///
+/// Linked worktrees and submodules use a `.git` file pointing to the real git directory;
+/// worktrees share the LFS storage of the main repository through `commondir`.
+fn find_git_dir(path: &Path) -> Option<PathBuf> {
+    let start = path.canonicalize().ok()?;
+    for dir in start.ancestors().skip(1) {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        if dot_git.is_file() {
+            let content = std::fs::read_to_string(&dot_git).ok()?;
+            let git_dir = dir.join(content.trim().strip_prefix("gitdir:")?.trim());
+            return Some(match std::fs::read_to_string(git_dir.join("commondir")) {
+                Ok(common_dir) => git_dir.join(common_dir.trim()),
+                Err(_) => git_dir,
+            });
+        }
+    }
+    None
+}
+
+/// Load the content of the LFS object referenced by the pointer file at `path`
+/// from the local LFS storage of the repository.
+///
+/// Returns [`Error::LFSMissing`](crate::Error::LFSMissing) if the object is not present locally.
+pub(crate) fn load_lfs_object(path: &Path, pointer: &LfsPointer) -> crate::Result<Vec<u8>> {
+    let git_dir = find_git_dir(path).ok_or(crate::Error::LFSMissing)?;
+    let object_path = git_dir
+        .join("lfs")
+        .join("objects")
+        .join(&pointer.oid[0..2])
+        .join(&pointer.oid[2..4])
+        .join(&pointer.oid);
+    log::debug!(
+        "Resolving LFS pointer {} from {}",
+        path.display(),
+        object_path.display()
+    );
+    if !object_path.is_file() {
+        return Err(crate::Error::LFSMissing);
+    }
+    let data = std::fs::read(&object_path)?;
+    let digest = Sha256::digest(&data);
+    let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+    if data.len() as u64 != pointer.size || hex != pointer.oid {
+        return Err(crate::Error::LFSObjectMismatch(object_path));
+    }
+    Ok(data)
+}
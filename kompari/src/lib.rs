@@ -24,9 +24,20 @@ use thiserror::Error;
 
 mod dirdiff;
 mod fsutils;
+#[cfg(feature = "git")]
+mod git;
 mod imageutils;
 mod imgdiff;
+mod journal;
+#[cfg(feature = "lfs")]
+mod lfs;
+#[cfg(feature = "manifest")]
+mod manifest;
 mod minimal_image;
+mod multidiff;
+mod pairing;
+mod snapshot;
+mod threeway;
 
 pub use crate::minimal_image::MinImage;
 
@@ -45,10 +56,22 @@ pub enum Error {
     FileNotFound(PathBuf),
 
     #[error("Image is unresolved LFS file. Maybe you need to install lfs - https://git-lfs.com/?")]
-    // TODO: My plan is that Kompari Tasks will catch this error at a higher level, and give a more detailed message for it
-    // This avoids spamming a really long message for each test.
+    // Kompari Tasks checks inputs before diffing and reports a missing LFS setup once,
+    // see `kompari_tasks::diagnose_inputs`.
     LFSMissing,
 
+    #[error("LFS object `{0}` does not match its pointer")]
+    LFSObjectMismatch(PathBuf),
+
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+
+    #[error("Invalid manifest: {0}")]
+    InvalidManifest(String),
+
+    #[error("Files `{0}` and `{1}` map to the same pair name")]
+    PairingConflict(PathBuf, PathBuf),
+
     #[error("Error `{0}`")]
     GenericError(Box<dyn std::error::Error + Send + Sync>),
 
@@ -58,9 +81,59 @@ pub enum Error {
     PngEncoding(#[from] png::EncodingError),
 }
 
+impl Error {
+    /// Name of the error variant, e.g. `FileNotFound`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::NotDirectory(_) => "NotDirectory",
+            Self::FileNotFound(_) => "FileNotFound",
+            Self::LFSMissing => "LFSMissing",
+            Self::LFSObjectMismatch(_) => "LFSObjectMismatch",
+            Self::InvalidPattern(_) => "InvalidPattern",
+            Self::InvalidManifest(_) => "InvalidManifest",
+            Self::PairingConflict(_, _) => "PairingConflict",
+            Self::GenericError(_) => "GenericError",
+            Self::PngDecoding(_) => "PngDecoding",
+            Self::PngEncoding(_) => "PngEncoding",
+        }
+    }
+}
+
+/// Errors are serialized as their kind and message.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Error {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("Error", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub use dirdiff::{DirDiff, DirDiffConfig, LeftRightError, PairResult};
-pub use fsutils::{list_image_dir, list_image_dir_names};
+pub use fsutils::{create_ignored_dir, list_image_dir, list_image_dir_names};
+#[cfg(feature = "git")]
+pub use git::GitRevisionDir;
 pub use imageutils::{SizeOptimizationLevel, bless_image, image_to_png, load_image, optimize_png};
+#[cfg(feature = "lfs")]
+pub use imageutils::{load_image_resolve_lfs, read_png_resolve_lfs};
 pub use imgdiff::{DiffImage, DiffImageMethod, ImageDifference, compare_images};
+pub use journal::{BlessJournal, UndoneBless};
+#[cfg(feature = "manifest")]
+pub use manifest::Manifest;
+pub use multidiff::{MultiDiff, MultiDiffConfig, MultiPairResult};
+pub use pairing::{NameMapping, Pair};
+pub use snapshot::{
+    SNAPSHOT_MODE_ENV, SnapshotConfig, SnapshotMode, SnapshotStatus, assert_snapshot,
+};
+pub use threeway::{
+    MergeStatus, ThreeWayDiff, ThreeWayDiffConfig, ThreeWayPairResult, ThreeWayResult,
+    compare_three_way, compare_three_way_files,
+};
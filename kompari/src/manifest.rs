@@ -0,0 +1,118 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::Pair;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// An explicit list of image pairs to compare.
+///
+/// A manifest is loaded from a JSON file containing an array of objects, or from a CSV file
+/// with a header row. Each entry has fields `title`, `left` and `right`, and an optional
+/// `tolerance` that overrides the pixel distance tolerance for that pair.
+///
+/// Relative paths are resolved against the directory containing the manifest file.
+///
+/// JSON example:
+///
+/// ```json
+/// [
+///     { "title": "circle", "left": "ref/circle.png", "right": "out/circle_gpu.png" },
+///     { "title": "text", "left": "ref/text.png", "right": "out/text.png", "tolerance": 2 }
+/// ]
+/// ```
+///
+/// CSV example:
+///
+/// ```csv
+/// title,left,right,tolerance
+/// circle,ref/circle.png,out/circle_gpu.png,
+/// text,ref/text.png,out/text.png,2
+/// ```
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pairs: Vec<Pair>,
+    path: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct ManifestEntry {
+    title: String,
+    left: PathBuf,
+    right: PathBuf,
+    #[serde(default)]
+    tolerance: Option<u8>,
+}
+
+impl Manifest {
+    /// Load a manifest; the format is chosen by the file extension (`.json` or `.csv`).
+    pub fn load(path: &Path) -> crate::Result<Self> {
+        if !path.is_file() {
+            return Err(crate::Error::FileNotFound(path.to_path_buf()));
+        }
+        let data = std::fs::read_to_string(path)?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        let mut manifest = match extension.as_deref() {
+            Some("json") => Self::from_json(&data, base_dir),
+            Some("csv") => Self::from_csv(&data, base_dir),
+            _ => Err(crate::Error::InvalidManifest(format!(
+                "unknown manifest format of `{}`, expected a .json or .csv file",
+                path.display()
+            ))),
+        }?;
+        manifest.path = Some(path.to_path_buf());
+        Ok(manifest)
+    }
+
+    /// Parse a manifest in JSON format; relative paths are resolved against `base_dir`.
+    pub fn from_json(data: &str, base_dir: &Path) -> crate::Result<Self> {
+        let entries: Vec<ManifestEntry> =
+            serde_json::from_str(data).map_err(|e| crate::Error::InvalidManifest(e.to_string()))?;
+        Self::from_entries(entries, base_dir)
+    }
+
+    /// Parse a manifest in CSV format; relative paths are resolved against `base_dir`.
+    pub fn from_csv(data: &str, base_dir: &Path) -> crate::Result<Self> {
+        let entries = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(data.as_bytes())
+            .deserialize()
+            .collect::<Result<Vec<ManifestEntry>, _>>()
+            .map_err(|e| crate::Error::InvalidManifest(e.to_string()))?;
+        Self::from_entries(entries, base_dir)
+    }
+
+    fn from_entries(entries: Vec<ManifestEntry>, base_dir: &Path) -> crate::Result<Self> {
+        let mut titles = HashSet::new();
+        let mut pairs = Vec::with_capacity(entries.len());
+        for entry in entries {
+            if !titles.insert(entry.title.clone()) {
+                return Err(crate::Error::InvalidManifest(format!(
+                    "duplicate title `{}`",
+                    entry.title
+                )));
+            }
+            pairs.push(Pair {
+                title: entry.title,
+                left: base_dir.join(entry.left),
+                right: base_dir.join(entry.right),
+                pixel_distance_tolerance: entry.tolerance,
+            });
+        }
+        Ok(Self { pairs, path: None })
+    }
+
+    pub fn pairs(&self) -> &[Pair] {
+        &self.pairs
+    }
+
+    /// File from which the manifest was loaded, `None` if it was parsed from data.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::dirdiff::compute_pair_diff;
+use crate::pairing::pairs_from_paths;
+use crate::{DirDiffConfig, Pair, PairResult};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Configuration of an N-way comparison, where images from several candidate directories
+/// are compared against one reference directory.
+///
+/// Pairing rules, tolerance and ignoring of missing files are taken from the inner
+/// [`DirDiffConfig`]; its left path is the reference directory and its right path is not used.
+/// The right name mapping is applied to every candidate directory.
+#[derive(Debug, Clone)]
+pub struct MultiDiffConfig {
+    diff_config: DirDiffConfig,
+    candidates: Vec<(String, PathBuf)>,
+}
+
+impl MultiDiffConfig {
+    pub fn new(reference_path: PathBuf) -> Self {
+        Self {
+            diff_config: DirDiffConfig::new(reference_path, PathBuf::new()),
+            candidates: Vec::new(),
+        }
+    }
+
+    pub fn reference_path(&self) -> &Path {
+        self.diff_config.left_path()
+    }
+
+    /// Settings shared by the comparisons of all candidates.
+    pub fn diff_config(&mut self) -> &mut DirDiffConfig {
+        &mut self.diff_config
+    }
+
+    pub fn add_candidate(&mut self, name: impl ToString, path: PathBuf) {
+        self.candidates.push((name.to_string(), path));
+    }
+
+    pub fn create_diff(&self) -> crate::Result<MultiDiff> {
+        let reference_path = self.diff_config.left_source_path();
+        let rules = self.diff_config.pairing_rules();
+        // Rows are keyed the same way as files are paired, so case-insensitive pairing
+        // also merges rows of different candidates.
+        let mut rows: BTreeMap<String, (String, Vec<Option<Pair>>)> = BTreeMap::new();
+        for (idx, (_, candidate_path)) in self.candidates.iter().enumerate() {
+            for pair in pairs_from_paths(reference_path, candidate_path, rules)? {
+                let row = rows
+                    .entry(rules.key(&pair.title))
+                    .or_insert_with(|| (pair.title.clone(), vec![None; self.candidates.len()]));
+                row.1[idx] = Some(pair);
+            }
+        }
+        let tolerance = self.diff_config.pixel_distance_tolerance();
+        let resolve_lfs = self.diff_config.resolve_lfs();
+        let results = rows
+            .into_values()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .filter_map(|(title, pairs)| {
+                // All candidates share the reference file
+                let reference = pairs
+                    .iter()
+                    .flatten()
+                    .next()
+                    .map(|pair| pair.left.clone())?;
+                let candidates: Vec<_> = pairs
+                    .into_iter()
+                    .zip(&self.candidates)
+                    .map(|(pair, (_, candidate_path))| {
+                        // The file is missing in this candidate, but is present in another one
+                        let pair = pair.unwrap_or_else(|| Pair {
+                            title: title.clone(),
+                            left: reference.clone(),
+                            right: candidate_path.join(rules.right_mapping.invert(&title)),
+                            pixel_distance_tolerance: None,
+                        });
+                        PairResult {
+                            image_diff: compute_pair_diff(&pair, tolerance, resolve_lfs),
+                            title: pair.title,
+                            left: pair.left,
+                            right: pair.right,
+                        }
+                    })
+                    .collect();
+                candidates
+                    .iter()
+                    .any(|result| self.diff_config.is_reported(&result.image_diff))
+                    .then_some(MultiPairResult {
+                        title,
+                        reference,
+                        candidates,
+                    })
+            })
+            .collect();
+        Ok(MultiDiff {
+            candidate_names: self
+                .candidates
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect(),
+            results,
+        })
+    }
+}
+
+/// Results of comparing one reference image against all candidates.
+#[derive(Debug)]
+pub struct MultiPairResult {
+    pub title: String,
+    pub reference: PathBuf,
+    /// One result per candidate, in the order in which candidates were added.
+    pub candidates: Vec<PairResult>,
+}
+
+#[derive(Default, Debug)]
+pub struct MultiDiff {
+    candidate_names: Vec<String>,
+    results: Vec<MultiPairResult>,
+}
+
+impl MultiDiff {
+    pub fn candidate_names(&self) -> &[String] {
+        &self.candidate_names
+    }
+
+    pub fn results(&self) -> &[MultiPairResult] {
+        &self.results
+    }
+}
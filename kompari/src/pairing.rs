@@ -0,0 +1,253 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::list_image_dir_names;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Transformation of a file name before files from the left and right directories are paired.
+///
+/// Files are paired when their transformed names are equal. The transformed name is also
+/// used as the title of the pair.
+#[derive(Debug, Clone, Default)]
+pub enum NameMapping {
+    /// Names are used as they are.
+    #[default]
+    Identity,
+    /// Strip `prefix` from the start and `suffix` from the end of the file stem,
+    /// e.g. `foo_expected.png` becomes `foo.png` for suffix `_expected`.
+    ///
+    /// Names that do not have the prefix or the suffix are used as they are.
+    Affix { prefix: String, suffix: String },
+    /// Replace the first match of `regex` in the relative path (with `/` as separator)
+    /// by `replacement`. The replacement may refer to capture groups, e.g. `$1`.
+    ///
+    /// Names that do not match are used as they are.
+    Regex { regex: Regex, replacement: String },
+}
+
+impl NameMapping {
+    pub fn affix(prefix: impl ToString, suffix: impl ToString) -> Self {
+        Self::Affix {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+        }
+    }
+
+    pub fn regex(pattern: &str, replacement: impl ToString) -> crate::Result<Self> {
+        let regex = Regex::new(pattern).map_err(|e| crate::Error::InvalidPattern(e.to_string()))?;
+        Ok(Self::Regex {
+            regex,
+            replacement: replacement.to_string(),
+        })
+    }
+
+    fn apply(&self, name: &str) -> String {
+        match self {
+            Self::Identity => name.to_string(),
+            Self::Affix { prefix, suffix } => {
+                let (dir, file_name) = split_file_name(name);
+                let (stem, ext) = split_extension(file_name);
+                let stem = stem.strip_prefix(prefix.as_str()).unwrap_or(stem);
+                let stem = stem.strip_suffix(suffix.as_str()).unwrap_or(stem);
+                format!("{dir}{stem}{ext}")
+            }
+            Self::Regex { regex, replacement } => {
+                regex.replace(name, replacement.as_str()).into_owned()
+            }
+        }
+    }
+
+    /// Creates a name for a file that is missing on this side.
+    /// Regular expressions cannot be inverted, so the name of the pair is used for them.
+    pub(crate) fn invert(&self, name: &str) -> String {
+        match self {
+            Self::Identity | Self::Regex { .. } => name.to_string(),
+            Self::Affix { prefix, suffix } => {
+                let (dir, file_name) = split_file_name(name);
+                let (stem, ext) = split_extension(file_name);
+                format!("{dir}{prefix}{stem}{suffix}{ext}")
+            }
+        }
+    }
+}
+
+fn split_file_name(name: &str) -> (&str, &str) {
+    name.rfind('/')
+        .map(|idx| name.split_at(idx + 1))
+        .unwrap_or(("", name))
+}
+
+fn split_extension(file_name: &str) -> (&str, &str) {
+    file_name
+        .rfind('.')
+        .filter(|idx| *idx > 0)
+        .map(|idx| file_name.split_at(idx))
+        .unwrap_or((file_name, ""))
+}
+
+/// Rules describing how files from the left and right directories are paired.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PairingRules {
+    pub left_mapping: NameMapping,
+    pub right_mapping: NameMapping,
+    pub flatten: bool,
+    pub case_insensitive: bool,
+    pub include_globs: Vec<String>,
+    pub exclude_globs: Vec<String>,
+    pub filter_name: Option<String>,
+}
+
+impl PairingRules {
+    fn build_globset(&self, patterns: &[String]) -> crate::Result<Option<GlobSet>> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = GlobBuilder::new(pattern)
+                .case_insensitive(self.case_insensitive)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| crate::Error::InvalidPattern(e.to_string()))?;
+            builder.add(glob);
+        }
+        builder
+            .build()
+            .map(Some)
+            .map_err(|e| crate::Error::InvalidPattern(e.to_string()))
+    }
+
+    /// Removes pairs whose titles are not selected by the filter and globs.
+    pub(crate) fn retain_matching(&self, pairs: &mut Vec<Pair>) -> crate::Result<()> {
+        let include = self.build_globset(&self.include_globs)?;
+        let exclude = self.build_globset(&self.exclude_globs)?;
+        pairs.retain(|pair| {
+            include
+                .as_ref()
+                .map(|g| g.is_match(&pair.title))
+                .unwrap_or(true)
+                && !exclude
+                    .as_ref()
+                    .map(|g| g.is_match(&pair.title))
+                    .unwrap_or(false)
+                && self
+                    .filter_name
+                    .as_ref()
+                    .map(|f| pair.title.contains(f))
+                    .unwrap_or(true)
+        });
+        Ok(())
+    }
+
+    fn pair_name(&self, name: &Path, mapping: &NameMapping) -> String {
+        let name = if self.flatten {
+            name.file_name().map(Path::new).unwrap_or(name)
+        } else {
+            name
+        };
+        mapping.apply(&path_to_slash(name))
+    }
+
+    pub(crate) fn key(&self, title: &str) -> String {
+        if self.case_insensitive {
+            title.to_lowercase()
+        } else {
+            title.to_string()
+        }
+    }
+}
+
+fn path_to_slash(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A pair of images that are compared with each other.
+///
+/// At least one of the files exists; the other one may be missing.
+#[derive(Debug, Clone)]
+pub struct Pair {
+    pub title: String,
+    pub left: PathBuf,
+    pub right: PathBuf,
+    /// Overrides the tolerance of [`DirDiffConfig`](crate::DirDiffConfig) for this pair.
+    pub pixel_distance_tolerance: Option<u8>,
+}
+
+#[derive(Default)]
+struct PairEntry {
+    title: String,
+    left: Option<PathBuf>,
+    right: Option<PathBuf>,
+}
+
+fn insert_names(
+    entries: &mut BTreeMap<String, PairEntry>,
+    dir_path: &Path,
+    rules: &PairingRules,
+    mapping: &NameMapping,
+    is_left: bool,
+) -> crate::Result<()> {
+    for name in list_image_dir_names(dir_path)? {
+        let title = rules.pair_name(&name, mapping);
+        let entry = entries.entry(rules.key(&title)).or_default();
+        let slot = if is_left {
+            &mut entry.left
+        } else {
+            &mut entry.right
+        };
+        if let Some(other) = slot {
+            return Err(crate::Error::PairingConflict(
+                dir_path.join(other),
+                dir_path.join(name),
+            ));
+        }
+        *slot = Some(name);
+        if entry.title.is_empty() {
+            entry.title = title;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn pairs_from_paths(
+    left_path: &Path,
+    right_path: &Path,
+    rules: &PairingRules,
+) -> crate::Result<Vec<Pair>> {
+    if !left_path.is_dir() {
+        return Err(crate::Error::NotDirectory(left_path.to_path_buf()));
+    }
+    if !right_path.is_dir() {
+        return Err(crate::Error::NotDirectory(right_path.to_path_buf()));
+    }
+    let mut entries = BTreeMap::new();
+    insert_names(&mut entries, left_path, rules, &rules.left_mapping, true)?;
+    insert_names(&mut entries, right_path, rules, &rules.right_mapping, false)?;
+
+    let mut pairs: Vec<_> = entries
+        .into_values()
+        .map(|entry| {
+            let left = entry
+                .left
+                .unwrap_or_else(|| rules.left_mapping.invert(&entry.title).into());
+            let right = entry
+                .right
+                .unwrap_or_else(|| rules.right_mapping.invert(&entry.title).into());
+            Pair {
+                title: entry.title,
+                left: left_path.join(left),
+                right: right_path.join(right),
+                pixel_distance_tolerance: None,
+            }
+        })
+        .collect();
+    pairs.sort_by(|a, b| a.title.cmp(&b.title));
+    rules.retain_matching(&mut pairs)?;
+    Ok(pairs)
+}
@@ -0,0 +1,272 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::imgdiff::{ImageDifference, compare_images};
+use crate::{MinImage, SizeOptimizationLevel, image_to_png, load_image};
+use std::path::{Path, PathBuf};
+
+/// Name of the environment variable selecting [`SnapshotMode`].
+pub const SNAPSHOT_MODE_ENV: &str = "KOMPARI_MODE";
+
+/// How snapshot assertions treat snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SnapshotMode {
+    /// Fail on missing or different snapshots (`verify`).
+    #[default]
+    Verify,
+    /// Write missing and different snapshots instead of failing (`overwrite`).
+    Overwrite,
+    /// Write missing snapshots, fail on different ones (`new-only`).
+    NewOnly,
+    /// Like `Verify`, but write every current image, so a report can show all of them
+    /// (`generate-all`).
+    GenerateAll,
+}
+
+impl SnapshotMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "verify" => Some(Self::Verify),
+            "overwrite" => Some(Self::Overwrite),
+            "new-only" => Some(Self::NewOnly),
+            "generate-all" => Some(Self::GenerateAll),
+            _ => None,
+        }
+    }
+
+    /// Read the mode from `KOMPARI_MODE`; `Verify` is used when the variable is not set.
+    pub fn from_env() -> crate::Result<Self> {
+        match std::env::var(SNAPSHOT_MODE_ENV) {
+            Ok(value) if !value.is_empty() => Self::parse(&value).ok_or_else(|| {
+                crate::Error::GenericError(
+                    format!(
+                        "Invalid {SNAPSHOT_MODE_ENV} `{value}`, \
+                         expected verify, overwrite, new-only or generate-all"
+                    )
+                    .into(),
+                )
+            }),
+            _ => Ok(Self::Verify),
+        }
+    }
+}
+
+/// Outcome of checking an image against its snapshot.
+#[derive(Debug)]
+pub enum SnapshotStatus {
+    Match,
+    /// The snapshot did not exist and was written.
+    Created,
+    /// The snapshot was different and was overwritten.
+    Updated,
+    /// The snapshot does not exist.
+    Missing,
+    Different(ImageDifference),
+}
+
+/// Configuration of snapshot assertions.
+///
+/// Snapshots are read from the snapshot directory; images that fail are written into
+/// the current directory, so the two directories can be compared by a report.
+#[derive(Debug, Clone)]
+pub struct SnapshotConfig {
+    snapshot_dir: PathBuf,
+    current_dir: PathBuf,
+    pixel_distance_tolerance: u8,
+    mode: Option<SnapshotMode>,
+    report_command: Option<String>,
+}
+
+impl Default for SnapshotConfig {
+    /// Uses `tests/snapshots` and `tests/current` relative to the working directory.
+    fn default() -> Self {
+        Self::new(
+            PathBuf::from("tests").join("snapshots"),
+            PathBuf::from("tests").join("current"),
+        )
+    }
+}
+
+impl SnapshotConfig {
+    pub fn new(snapshot_dir: PathBuf, current_dir: PathBuf) -> Self {
+        Self {
+            snapshot_dir,
+            current_dir,
+            pixel_distance_tolerance: 0,
+            mode: None,
+            report_command: None,
+        }
+    }
+
+    /// Uses `tests/snapshots` and `tests/current` in the given directory,
+    /// usually `env!("CARGO_MANIFEST_DIR")`.
+    pub fn in_dir(dir: impl AsRef<Path>) -> Self {
+        let tests = dir.as_ref().join("tests");
+        Self::new(tests.join("snapshots"), tests.join("current"))
+    }
+
+    pub fn snapshot_dir(&self) -> &Path {
+        &self.snapshot_dir
+    }
+
+    pub fn current_dir(&self) -> &Path {
+        &self.current_dir
+    }
+
+    pub fn set_pixel_distance_tolerance(&mut self, value: u8) {
+        self.pixel_distance_tolerance = value;
+    }
+
+    /// Override the mode; if not set, it is read from `KOMPARI_MODE`.
+    pub fn set_mode(&mut self, value: Option<SnapshotMode>) {
+        self.mode = value;
+    }
+
+    /// Command suggested in failure messages, e.g. `cargo xtask report`.
+    pub fn set_report_command(&mut self, value: Option<String>) {
+        self.report_command = value;
+    }
+
+    fn write_current(&self, image: &MinImage, name: &str) -> crate::Result<PathBuf> {
+        let path = self.current_dir.join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, image_to_png(image, SizeOptimizationLevel::None))?;
+        Ok(path)
+    }
+
+    fn write_snapshot(&self, image: &MinImage, name: &str) -> crate::Result<()> {
+        let path = self.snapshot_dir.join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, image_to_png(image, SizeOptimizationLevel::High))?;
+        Ok(())
+    }
+
+    /// Compare an image with the snapshot `name` and write images according to the mode.
+    pub fn check(&self, image: &MinImage, name: &str) -> crate::Result<SnapshotStatus> {
+        let mode = match self.mode {
+            Some(mode) => mode,
+            None => SnapshotMode::from_env()?,
+        };
+        if mode == SnapshotMode::GenerateAll {
+            self.write_current(image, name)?;
+        }
+        let snapshot_path = self.snapshot_dir.join(name);
+        if !snapshot_path.exists() {
+            return Ok(match mode {
+                SnapshotMode::Overwrite | SnapshotMode::NewOnly => {
+                    self.write_snapshot(image, name)?;
+                    SnapshotStatus::Created
+                }
+                SnapshotMode::Verify | SnapshotMode::GenerateAll => {
+                    self.write_current(image, name)?;
+                    SnapshotStatus::Missing
+                }
+            });
+        }
+        let snapshot = load_image(&snapshot_path)?;
+        let difference = compare_images(&snapshot, image, self.pixel_distance_tolerance);
+        Ok(match (difference, mode) {
+            (ImageDifference::None, _) => SnapshotStatus::Match,
+            (_, SnapshotMode::Overwrite) => {
+                self.write_snapshot(image, name)?;
+                SnapshotStatus::Updated
+            }
+            (difference, _) => {
+                self.write_current(image, name)?;
+                SnapshotStatus::Different(difference)
+            }
+        })
+    }
+
+    fn failure_message(&self, name: &str, status: &SnapshotStatus) -> Option<String> {
+        let snapshot_path = self.snapshot_dir.join(name);
+        let mut message = match status {
+            SnapshotStatus::Match | SnapshotStatus::Created | SnapshotStatus::Updated => {
+                return None;
+            }
+            SnapshotStatus::Missing => {
+                format!("Snapshot `{}` does not exist", snapshot_path.display())
+            }
+            SnapshotStatus::Different(ImageDifference::SizeMismatch {
+                left_size,
+                right_size,
+            }) => format!(
+                "Snapshot `{}` differs in size: expected {}x{}, got {}x{}",
+                snapshot_path.display(),
+                left_size.0,
+                left_size.1,
+                right_size.0,
+                right_size.1
+            ),
+            SnapshotStatus::Different(ImageDifference::Content {
+                n_pixels,
+                n_different_pixels,
+                distance_sum,
+                ..
+            }) => {
+                let pct = *n_different_pixels as f32 / *n_pixels as f32 * 100.0;
+                let distance_sum = *distance_sum as f32 / 255.0;
+                format!(
+                    "Snapshot `{}` differs: {n_different_pixels} of {n_pixels} pixels \
+                     ({pct:.1}%), color distance {distance_sum:.3}",
+                    snapshot_path.display(),
+                )
+            }
+            SnapshotStatus::Different(ImageDifference::None) => unreachable!(),
+        };
+        message.push_str(&format!(
+            "\nCurrent image was written into `{}`",
+            self.current_dir.join(name).display()
+        ));
+        let report_command = self.report_command.clone().unwrap_or_else(|| {
+            format!(
+                "kompari report {} {}",
+                self.snapshot_dir.display(),
+                self.current_dir.display()
+            )
+        });
+        message.push_str(&format!(
+            "\nRun `{report_command}` to see the report, or set {SNAPSHOT_MODE_ENV}=overwrite \
+             to accept the new image"
+        ));
+        Some(message)
+    }
+
+    /// Check an image against the snapshot `name`; panics with diff statistics on failure.
+    #[track_caller]
+    pub fn assert_snapshot(&self, image: &MinImage, name: &str) {
+        let status = match self.check(image, name) {
+            Ok(status) => status,
+            Err(e) => panic!("Snapshot `{name}` could not be checked: {e}"),
+        };
+        if let Some(message) = self.failure_message(name, &status) {
+            panic!("{message}");
+        }
+    }
+}
+
+/// Check an image against the snapshot `name` in `tests/snapshots` of the working directory.
+///
+/// See [`SnapshotConfig`] for other directories and a tolerance,
+/// and [`assert_snapshot!`](crate::assert_snapshot!) for directories of the calling crate.
+#[track_caller]
+pub fn assert_snapshot(image: &MinImage, name: &str) {
+    SnapshotConfig::default().assert_snapshot(image, name);
+}
+
+/// Check an image against a snapshot in `tests/snapshots` of the calling crate.
+///
+/// ```no_run
+/// # let image = kompari::MinImage { width: 0, height: 0, data: vec![] };
+/// kompari::assert_snapshot!(image, "circle.png");
+/// ```
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($image:expr, $name:expr $(,)?) => {
+        $crate::SnapshotConfig::in_dir(env!("CARGO_MANIFEST_DIR")).assert_snapshot(&$image, $name)
+    };
+}
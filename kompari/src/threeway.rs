@@ -0,0 +1,214 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::imgdiff::{ImageDifference, compare_images};
+use crate::{LeftRightError, MinImage, list_image_dir_names, load_image};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::path::{Path, PathBuf};
+
+/// Classification of an image changed on two branches against their common base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStatus {
+    /// Neither side changed the image.
+    Unchanged,
+    /// Only "ours" changed the image; "ours" can be taken.
+    ChangedInOurs,
+    /// Only "theirs" changed the image; "theirs" can be taken.
+    ChangedInTheirs,
+    /// Both sides changed the image in the same way; either side can be taken.
+    BothChangedIdentical,
+    /// Both sides changed the image differently.
+    Conflict,
+}
+
+impl MergeStatus {
+    /// Returns `true` if the image can be merged without a manual decision.
+    pub fn is_resolvable(&self) -> bool {
+        !matches!(self, Self::Conflict)
+    }
+}
+
+#[derive(Debug)]
+pub struct ThreeWayResult {
+    pub status: MergeStatus,
+    /// Difference between base and ours; `None` when there is no base.
+    pub base_ours: Option<ImageDifference>,
+    /// Difference between base and theirs; `None` when there is no base.
+    pub base_theirs: Option<ImageDifference>,
+    pub ours_theirs: ImageDifference,
+}
+
+/// Compare two versions of an image derived from a common base.
+///
+/// `base` is `None` if the image was added on both sides.
+pub fn compare_three_way(
+    base: Option<&MinImage>,
+    ours: &MinImage,
+    theirs: &MinImage,
+    pixel_distance_tolerance: u8,
+) -> ThreeWayResult {
+    let ours_theirs = compare_images(ours, theirs, pixel_distance_tolerance);
+    let Some(base) = base else {
+        let status = if matches!(ours_theirs, ImageDifference::None) {
+            MergeStatus::BothChangedIdentical
+        } else {
+            MergeStatus::Conflict
+        };
+        return ThreeWayResult {
+            status,
+            base_ours: None,
+            base_theirs: None,
+            ours_theirs,
+        };
+    };
+    let base_ours = compare_images(base, ours, pixel_distance_tolerance);
+    let base_theirs = compare_images(base, theirs, pixel_distance_tolerance);
+    let status = match (
+        matches!(base_ours, ImageDifference::None),
+        matches!(base_theirs, ImageDifference::None),
+        matches!(ours_theirs, ImageDifference::None),
+    ) {
+        (true, true, _) => MergeStatus::Unchanged,
+        (false, true, _) => MergeStatus::ChangedInOurs,
+        (true, false, _) => MergeStatus::ChangedInTheirs,
+        (false, false, true) => MergeStatus::BothChangedIdentical,
+        (false, false, false) => MergeStatus::Conflict,
+    };
+    ThreeWayResult {
+        status,
+        base_ours: Some(base_ours),
+        base_theirs: Some(base_theirs),
+        ours_theirs,
+    }
+}
+
+/// Load the three versions of an image and compare them.
+///
+/// A missing or undecodable base is treated as no base, as git passes an empty file
+/// when both sides added the image. Errors of "ours" are reported as left errors and
+/// errors of "theirs" as right errors.
+pub fn compare_three_way_files(
+    base: &Path,
+    ours: &Path,
+    theirs: &Path,
+    pixel_distance_tolerance: u8,
+) -> Result<ThreeWayResult, LeftRightError> {
+    let base = load_image(base)
+        .inspect_err(|e| log::debug!("Base image not used: {e}"))
+        .ok();
+    let (ours, theirs) = match (load_image(ours), load_image(theirs)) {
+        (Ok(ours), Ok(theirs)) => (ours, theirs),
+        (Err(e), Ok(_)) => return Err(LeftRightError::Left(e)),
+        (Ok(_), Err(e)) => return Err(LeftRightError::Right(e)),
+        (Err(e1), Err(e2)) => return Err(LeftRightError::Both(Box::new((e1, e2)))),
+    };
+    Ok(compare_three_way(
+        base.as_ref(),
+        &ours,
+        &theirs,
+        pixel_distance_tolerance,
+    ))
+}
+
+/// Configuration of a three-way comparison of snapshot directories.
+#[derive(Debug, Clone)]
+pub struct ThreeWayDiffConfig {
+    base_path: PathBuf,
+    ours_path: PathBuf,
+    theirs_path: PathBuf,
+    filter_name: Option<String>,
+    pixel_distance_tolerance: u8,
+}
+
+impl ThreeWayDiffConfig {
+    pub fn new(base_path: PathBuf, ours_path: PathBuf, theirs_path: PathBuf) -> Self {
+        Self {
+            base_path,
+            ours_path,
+            theirs_path,
+            filter_name: None,
+            pixel_distance_tolerance: 0,
+        }
+    }
+
+    pub fn set_filter_name(&mut self, value: Option<String>) {
+        self.filter_name = value;
+    }
+
+    pub fn set_pixel_distance_tolerance(&mut self, value: u8) {
+        self.pixel_distance_tolerance = value;
+    }
+
+    /// Compare all images found in any of the three directories.
+    /// Images unchanged on both sides are not included in the result.
+    pub fn create_diff(&self) -> crate::Result<ThreeWayDiff> {
+        let mut names = Vec::new();
+        for path in [&self.base_path, &self.ours_path, &self.theirs_path] {
+            if !path.is_dir() {
+                return Err(crate::Error::NotDirectory(path.to_path_buf()));
+            }
+            names.extend(list_image_dir_names(path)?);
+        }
+        names.sort_unstable();
+        names.dedup();
+        names.retain(|name| {
+            self.filter_name
+                .as_ref()
+                .map(|f| name.to_string_lossy().contains(f))
+                .unwrap_or(true)
+        });
+        let results = names
+            .into_par_iter()
+            .filter_map(|name| {
+                let base = self.base_path.join(&name);
+                let ours = self.ours_path.join(&name);
+                let theirs = self.theirs_path.join(&name);
+                let result =
+                    compare_three_way_files(&base, &ours, &theirs, self.pixel_distance_tolerance);
+                if matches!(&result, Ok(r) if r.status == MergeStatus::Unchanged) {
+                    return None;
+                }
+                Some(ThreeWayPairResult {
+                    title: name.to_string_lossy().to_string(),
+                    base,
+                    ours,
+                    theirs,
+                    result,
+                })
+            })
+            .collect();
+        Ok(ThreeWayDiff { results })
+    }
+}
+
+#[derive(Debug)]
+pub struct ThreeWayPairResult {
+    pub title: String,
+    pub base: PathBuf,
+    pub ours: PathBuf,
+    pub theirs: PathBuf,
+    /// Error if "ours" (left) or "theirs" (right) could not be loaded.
+    pub result: Result<ThreeWayResult, LeftRightError>,
+}
+
+#[derive(Default, Debug)]
+pub struct ThreeWayDiff {
+    results: Vec<ThreeWayPairResult>,
+}
+
+impl ThreeWayDiff {
+    pub fn from_results(results: Vec<ThreeWayPairResult>) -> Self {
+        Self { results }
+    }
+
+    pub fn results(&self) -> &[ThreeWayPairResult] {
+        &self.results
+    }
+
+    /// Returns `true` if any image has a conflict or could not be loaded.
+    pub fn has_conflicts(&self) -> bool {
+        self.results
+            .iter()
+            .any(|r| !matches!(&r.result, Ok(r) if r.status.is_resolvable()))
+    }
+}
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 use kompari::color::Rgba8;
-use kompari::{DirDiffConfig, ImageDifference, LeftRightError, MinImage, compare_images};
+use kompari::{
+    DirDiffConfig, ImageDifference, LeftRightError, MergeStatus, MinImage, MultiDiffConfig,
+    compare_images, compare_three_way,
+};
 use std::path::Path;
 
 fn create_test_diff_config() -> DirDiffConfig {
@@ -77,6 +80,20 @@ pub(crate) fn test_compare_dir() {
     ));
 }
 
+#[test]
+fn test_include_matches() {
+    let mut config = create_test_diff_config();
+    config.set_include_matches(true);
+    let diff = config.create_diff().unwrap();
+    let same = diff
+        .results()
+        .iter()
+        .find(|r| r.title == "same.png")
+        .unwrap();
+    assert!(matches!(same.image_diff, Ok(ImageDifference::None)));
+    assert_eq!(diff.results().len(), 8);
+}
+
 #[test]
 pub(crate) fn test_ignore_left_missing() {
     let mut config = create_test_diff_config();
@@ -146,3 +163,105 @@ fn test_pixel_distance_tolerance() {
         }
     ));
 }
+
+#[test]
+fn test_multi_diff() {
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("tests");
+    let mut config = MultiDiffConfig::new(test_dir.join("left"));
+    config.add_candidate("right", test_dir.join("right"));
+    config.add_candidate("left", test_dir.join("left"));
+    config.diff_config().set_filter_name(Some("t".into()));
+    let diff = config.create_diff().unwrap();
+    assert_eq!(diff.candidate_names(), ["right", "left"]);
+    let res = diff.results();
+    let titles: Vec<_> = res.iter().map(|r| r.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        [
+            "bright.png",
+            "changetext.png",
+            "left_missing.png",
+            "right_missing.png",
+            "shift.png",
+        ]
+    );
+    assert!(matches!(
+        res[0].candidates[0].image_diff,
+        Ok(ImageDifference::Content {
+            n_different_pixels: 18623,
+            ..
+        })
+    ));
+    assert!(matches!(
+        res[0].candidates[1].image_diff,
+        Ok(ImageDifference::None)
+    ));
+    assert!(matches!(
+        res[2].candidates[0].image_diff,
+        Err(LeftRightError::Left(kompari::Error::FileNotFound(_)))
+    ));
+    assert!(matches!(
+        res[2].candidates[1].image_diff,
+        Err(LeftRightError::Both(_))
+    ));
+    assert_eq!(
+        res[3].reference,
+        test_dir.join("left").join("right_missing.png")
+    );
+    assert!(matches!(
+        res[3].candidates[1].image_diff,
+        Ok(ImageDifference::None)
+    ));
+}
+
+#[test]
+fn test_three_way() {
+    let image = |values: [u8; 2]| MinImage {
+        width: 2,
+        height: 1,
+        data: values
+            .iter()
+            .map(|&r| Rgba8 {
+                r,
+                g: 0,
+                b: 0,
+                a: 255,
+            })
+            .collect(),
+    };
+    let base = image([0, 0]);
+    let changed = image([0, 10]);
+    let other = image([10, 0]);
+    let status = |base: Option<&MinImage>, ours, theirs, tolerance| {
+        compare_three_way(base, ours, theirs, tolerance).status
+    };
+    assert_eq!(status(Some(&base), &base, &base, 0), MergeStatus::Unchanged);
+    assert_eq!(
+        status(Some(&base), &changed, &base, 0),
+        MergeStatus::ChangedInOurs
+    );
+    assert_eq!(
+        status(Some(&base), &base, &changed, 0),
+        MergeStatus::ChangedInTheirs
+    );
+    assert_eq!(
+        status(Some(&base), &changed, &changed, 0),
+        MergeStatus::BothChangedIdentical
+    );
+    assert_eq!(
+        status(Some(&base), &changed, &other, 0),
+        MergeStatus::Conflict
+    );
+    assert_eq!(
+        status(Some(&base), &changed, &other, 10),
+        MergeStatus::Unchanged
+    );
+    assert_eq!(
+        status(None, &changed, &changed, 0),
+        MergeStatus::BothChangedIdentical
+    );
+    assert_eq!(status(None, &changed, &other, 0), MergeStatus::Conflict);
+}
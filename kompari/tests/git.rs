@@ -0,0 +1,61 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "git")]
+
+use kompari::{DirDiffConfig, ImageDifference, LeftRightError};
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(workdir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(workdir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn test_left_revision() {
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("tests");
+    let repo = TempDir::new().unwrap();
+    let snapshots = repo.path().join("tests").join("snapshots");
+    std::fs::create_dir_all(&snapshots).unwrap();
+    std::fs::copy(test_dir.join("left/bright.png"), snapshots.join("a.png")).unwrap();
+    std::fs::copy(test_dir.join("left/same.png"), snapshots.join("b.png")).unwrap();
+    git(repo.path(), &["init", "-q"]);
+    git(repo.path(), &["add", "."]);
+    git(repo.path(), &["commit", "-q", "-m", "Snapshots"]);
+
+    // Re-bless one snapshot and add another one in the working tree
+    std::fs::copy(test_dir.join("right/bright.png"), snapshots.join("a.png")).unwrap();
+    std::fs::copy(test_dir.join("left/shift.png"), snapshots.join("c.png")).unwrap();
+
+    let mut config = DirDiffConfig::new(snapshots.clone(), snapshots.clone());
+    config.set_left_revision(Some("HEAD")).unwrap();
+    assert_eq!(config.left_revision(), Some("HEAD"));
+    assert_eq!(config.left_path(), snapshots);
+    let diff = config.create_diff().unwrap();
+    let res = diff.results();
+    let titles: Vec<_> = res.iter().map(|r| r.title.as_str()).collect();
+    assert_eq!(titles, ["a.png", "c.png"]);
+    assert!(matches!(
+        res[0].image_diff,
+        Ok(ImageDifference::Content {
+            n_different_pixels: 18623,
+            ..
+        })
+    ));
+    assert!(matches!(
+        res[1].image_diff,
+        Err(LeftRightError::Left(kompari::Error::FileNotFound(_)))
+    ));
+
+    assert!(config.set_left_revision(Some("no-such-branch")).is_err());
+}
@@ -0,0 +1,110 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use kompari::{BlessJournal, load_image};
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn test_bless_and_undo() {
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("tests");
+    let workdir = TempDir::new().unwrap();
+    let snapshots = workdir.path().join("snapshots");
+    std::fs::create_dir(&snapshots).unwrap();
+    let target = snapshots.join("bright.png");
+    let new_target = snapshots.join("new.png");
+    std::fs::copy(test_dir.join("left/bright.png"), &target).unwrap();
+    let original = std::fs::read(&target).unwrap();
+
+    let journal = BlessJournal::for_target_dir(&snapshots);
+    assert_eq!(
+        journal.dir(),
+        workdir.path().join(".snapshots.kompari-journal")
+    );
+    let source = test_dir.join("right/bright.png");
+    journal
+        .bless(&[(&source, &target), (&source, &new_target)])
+        .unwrap();
+    assert_eq!(journal.len().unwrap(), 1);
+    assert!(journal.dir().join(".gitignore").is_file());
+    assert_eq!(
+        load_image(&target).unwrap().data,
+        load_image(&source).unwrap().data
+    );
+    assert!(new_target.is_file());
+
+    let undone = journal.undo().unwrap().unwrap();
+    assert_eq!(undone.paths.len(), 2);
+    assert_eq!(undone.data, None);
+    assert_eq!(std::fs::read(&target).unwrap(), original);
+    assert!(!new_target.exists());
+    assert!(journal.is_empty().unwrap());
+    assert!(journal.undo().unwrap().is_none());
+}
+
+#[test]
+fn test_bless_skips_unchanged_pixels() {
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("tests");
+    let workdir = TempDir::new().unwrap();
+    let target = workdir.path().join("same.png");
+    // Same pixels, but different encoding than the source
+    let image = load_image(&test_dir.join("right/same.png")).unwrap();
+    std::fs::write(
+        &target,
+        kompari::image_to_png(&image, kompari::SizeOptimizationLevel::None),
+    )
+    .unwrap();
+    let original = std::fs::read(&target).unwrap();
+
+    let journal = BlessJournal::new(workdir.path().join("journal"));
+    let source = test_dir.join("right/same.png");
+    assert!(!kompari::bless_image(&source, &target).unwrap());
+    assert_eq!(journal.bless(&[(&source, &target)]).unwrap(), 0);
+    assert_eq!(std::fs::read(&target).unwrap(), original);
+
+    let source = test_dir.join("right/bright.png");
+    assert_eq!(journal.bless(&[(&source, &target)]).unwrap(), 1);
+    assert_eq!(
+        load_image(&target).unwrap().data,
+        load_image(&source).unwrap().data
+    );
+}
+
+#[test]
+fn test_journal_max_entries() {
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("tests");
+    let workdir = TempDir::new().unwrap();
+    let target = workdir.path().join("image.png");
+    let mut journal = BlessJournal::new(workdir.path().join("journal"));
+    journal.set_max_entries(2);
+    let sources = ["left/bright.png", "right/bright.png", "left/shift.png"];
+    for source in sources {
+        journal
+            .bless_with_data(&[(&test_dir.join(source), &target)], Some(source))
+            .unwrap();
+    }
+    assert_eq!(journal.len().unwrap(), 2);
+
+    // Only the newest entries can be undone
+    let undone = journal.undo().unwrap().unwrap();
+    assert_eq!(undone.data.as_deref(), Some(sources[2]));
+    assert_eq!(
+        load_image(&target).unwrap().data,
+        load_image(&test_dir.join(sources[1])).unwrap().data
+    );
+    journal.undo().unwrap().unwrap();
+    assert_eq!(
+        load_image(&target).unwrap().data,
+        load_image(&test_dir.join(sources[0])).unwrap().data
+    );
+    assert!(journal.undo().unwrap().is_none());
+}
@@ -0,0 +1,100 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "lfs")]
+
+use kompari::{DirDiffConfig, ImageDifference, load_image, load_image_resolve_lfs};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn test_dir() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("tests")
+}
+
+fn test_image(name: &str) -> Vec<u8> {
+    std::fs::read(test_dir().join(name)).unwrap()
+}
+
+/// Write a pointer file for `data` and return the path where the LFS object belongs
+fn write_pointer(repo: &Path, path: &Path, data: &[u8]) -> std::path::PathBuf {
+    let oid: String = Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    std::fs::write(
+        path,
+        format!(
+            "version https://git-lfs.github.com/spec/v1\noid sha256:{oid}\nsize {}\n",
+            data.len()
+        ),
+    )
+    .unwrap();
+    let object_dir = repo
+        .join(".git/lfs/objects")
+        .join(&oid[0..2])
+        .join(&oid[2..4]);
+    std::fs::create_dir_all(&object_dir).unwrap();
+    object_dir.join(oid)
+}
+
+#[test]
+fn test_resolve_lfs_pointer() {
+    let repo = TempDir::new().unwrap();
+    let snapshots = repo.path().join("snapshots");
+    std::fs::create_dir_all(&snapshots).unwrap();
+    let pointer = snapshots.join("bright.png");
+    let data = test_image("left/bright.png");
+    let object = write_pointer(repo.path(), &pointer, &data);
+
+    // The object is not in the local storage
+    assert!(matches!(
+        load_image_resolve_lfs(&pointer),
+        Err(kompari::Error::LFSMissing)
+    ));
+
+    std::fs::write(&object, &data).unwrap();
+    assert!(matches!(
+        load_image(&pointer),
+        Err(kompari::Error::LFSMissing)
+    ));
+    let image = load_image_resolve_lfs(&pointer).unwrap();
+    let expected = load_image(&test_dir().join("left/bright.png")).unwrap();
+    assert_eq!(
+        (image.width, image.height),
+        (expected.width, expected.height)
+    );
+    assert_eq!(image.data, expected.data);
+
+    let current = TempDir::new().unwrap();
+    std::fs::write(
+        current.path().join("bright.png"),
+        test_image("right/bright.png"),
+    )
+    .unwrap();
+    let mut config = DirDiffConfig::new(snapshots, current.path().to_path_buf());
+    config.set_resolve_lfs(true);
+    let diff = config.create_diff().unwrap();
+    assert!(matches!(
+        diff.results()[0].image_diff,
+        Ok(ImageDifference::Content {
+            n_different_pixels: 18623,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_corrupted_lfs_object() {
+    let repo = TempDir::new().unwrap();
+    let pointer = repo.path().join("bright.png");
+    let object = write_pointer(repo.path(), &pointer, &test_image("left/bright.png"));
+    std::fs::write(&object, test_image("right/bright.png")).unwrap();
+    assert!(matches!(
+        load_image_resolve_lfs(&pointer),
+        Err(kompari::Error::LFSObjectMismatch(_))
+    ));
+}
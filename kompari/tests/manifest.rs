@@ -0,0 +1,81 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "manifest")]
+
+use kompari::{DirDiffConfig, ImageDifference, LeftRightError, Manifest};
+use std::path::Path;
+
+fn test_dir() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("tests")
+}
+
+#[test]
+fn test_json_manifest() {
+    let manifest = Manifest::from_json(
+        r#"[
+            {"title": "renamed", "left": "left/bright.png", "right": "right/bright.png"},
+            {"title": "same", "left": "left/same.png", "right": "right/same.png"},
+            {"title": "tolerated", "left": "left/bright.png", "right": "right/bright.png", "tolerance": 255},
+            {"title": "missing", "left": "left/left_missing.png", "right": "right/left_missing.png"}
+        ]"#,
+        &test_dir(),
+    )
+    .unwrap();
+    let mut config = DirDiffConfig::new("unused-left".into(), "unused-right".into());
+    config.set_manifest(Some(manifest));
+    let diff = config.create_diff().unwrap();
+    let res = diff.results();
+    let titles: Vec<_> = res.iter().map(|r| r.title.as_str()).collect();
+    // "same" matches and "tolerated" is within its tolerance
+    assert_eq!(titles, ["renamed", "missing"]);
+    assert!(matches!(
+        res[0].image_diff,
+        Ok(ImageDifference::Content {
+            n_different_pixels: 18623,
+            ..
+        })
+    ));
+    assert!(matches!(
+        res[1].image_diff,
+        Err(LeftRightError::Left(kompari::Error::FileNotFound(_)))
+    ));
+}
+
+#[test]
+fn test_csv_manifest() {
+    let manifest = Manifest::from_csv(
+        "title,left,right,tolerance\n\
+         a,left/bright.png,right/bright.png,\n\
+         b, left/shift.png , right/shift.png,3\n",
+        &test_dir(),
+    )
+    .unwrap();
+    let pairs = manifest.pairs();
+    assert_eq!(pairs.len(), 2);
+    assert_eq!(pairs[0].title, "a");
+    assert_eq!(pairs[0].pixel_distance_tolerance, None);
+    assert_eq!(pairs[1].left, test_dir().join("left/shift.png"));
+    assert_eq!(pairs[1].pixel_distance_tolerance, Some(3));
+
+    let manifest = Manifest::from_csv("title,left,right\na,x.png,y.png\n", &test_dir()).unwrap();
+    assert_eq!(manifest.pairs()[0].pixel_distance_tolerance, None);
+}
+
+#[test]
+fn test_invalid_manifest() {
+    assert!(matches!(
+        Manifest::from_json(
+            r#"[{"title": "a", "left": "x", "right": "y"}, {"title": "a", "left": "x", "right": "z"}]"#,
+            &test_dir()
+        ),
+        Err(kompari::Error::InvalidManifest(_))
+    ));
+    assert!(matches!(
+        Manifest::from_csv("title,left\na,x.png\n", &test_dir()),
+        Err(kompari::Error::InvalidManifest(_))
+    ));
+}
@@ -0,0 +1,131 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use kompari::color::Rgba8;
+use kompari::{DirDiffConfig, ImageDifference, LeftRightError, MinImage, NameMapping};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_image(path: &Path, r: u8) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let image = MinImage {
+        width: 1,
+        height: 1,
+        data: vec![Rgba8 {
+            r,
+            g: 0,
+            b: 0,
+            a: 255,
+        }],
+    };
+    let mut file = std::fs::File::create(path).unwrap();
+    image.encode_to_png(&mut file).unwrap();
+}
+
+fn titles(config: &DirDiffConfig) -> Vec<String> {
+    config
+        .pairs()
+        .unwrap()
+        .into_iter()
+        .map(|pair| pair.title)
+        .collect()
+}
+
+#[test]
+fn test_affix_mapping() {
+    let left = TempDir::new().unwrap();
+    let right = TempDir::new().unwrap();
+    write_image(&left.path().join("foo_expected.png"), 0);
+    write_image(&left.path().join("bar_expected.png"), 0);
+    write_image(&right.path().join("foo_actual.png"), 1);
+    write_image(&right.path().join("baz_actual.png"), 0);
+
+    let mut config = DirDiffConfig::new(left.path().to_path_buf(), right.path().to_path_buf());
+    config.set_left_name_mapping(NameMapping::affix("", "_expected"));
+    config.set_right_name_mapping(NameMapping::affix("", "_actual"));
+    let pairs = config.pairs().unwrap();
+    let titles: Vec<_> = pairs.iter().map(|p| p.title.as_str()).collect();
+    assert_eq!(titles, ["bar.png", "baz.png", "foo.png"]);
+    assert_eq!(pairs[0].right, right.path().join("bar_actual.png"));
+    assert_eq!(pairs[1].left, left.path().join("baz_expected.png"));
+    assert_eq!(pairs[2].left, left.path().join("foo_expected.png"));
+    assert_eq!(pairs[2].right, right.path().join("foo_actual.png"));
+
+    let diff = config.create_diff().unwrap();
+    let res = diff.results();
+    assert!(matches!(
+        res[0].image_diff,
+        Err(LeftRightError::Right(kompari::Error::FileNotFound(_)))
+    ));
+    assert!(matches!(
+        res[2].image_diff,
+        Ok(ImageDifference::Content {
+            n_different_pixels: 1,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_regex_mapping_and_flatten() {
+    let left = TempDir::new().unwrap();
+    let right = TempDir::new().unwrap();
+    write_image(&left.path().join("scene1.png"), 0);
+    write_image(&left.path().join("scene2.png"), 0);
+    write_image(&right.path().join("cpu").join("scene1-cpu.png"), 0);
+    write_image(&right.path().join("cpu").join("scene2-cpu.png"), 0);
+
+    let mut config = DirDiffConfig::new(left.path().to_path_buf(), right.path().to_path_buf());
+    config.set_right_name_mapping(NameMapping::regex(r"^(.*)-cpu\.png$", "$1.png").unwrap());
+    config.set_flatten(true);
+    assert_eq!(titles(&config), ["scene1.png", "scene2.png"]);
+
+    config.set_flatten(false);
+    assert_eq!(
+        titles(&config),
+        [
+            "cpu/scene1.png",
+            "cpu/scene2.png",
+            "scene1.png",
+            "scene2.png"
+        ]
+    );
+}
+
+#[test]
+fn test_case_insensitive_and_globs() {
+    let left = TempDir::new().unwrap();
+    let right = TempDir::new().unwrap();
+    write_image(&left.path().join("Foo.png"), 0);
+    write_image(&left.path().join("sub").join("bar.png"), 0);
+    write_image(&right.path().join("foo.PNG"), 0);
+    write_image(&right.path().join("sub").join("bar.png"), 0);
+
+    let mut config = DirDiffConfig::new(left.path().to_path_buf(), right.path().to_path_buf());
+    assert_eq!(titles(&config), ["Foo.png", "foo.PNG", "sub/bar.png"]);
+
+    config.set_case_insensitive(true);
+    assert_eq!(titles(&config), ["Foo.png", "sub/bar.png"]);
+
+    config.set_include_globs(vec!["sub/*".into()]);
+    assert_eq!(titles(&config), ["sub/bar.png"]);
+
+    config.set_include_globs(vec![]);
+    config.set_exclude_globs(vec!["*.png".into()]);
+    assert_eq!(titles(&config), ["sub/bar.png"]);
+}
+
+#[test]
+fn test_pairing_conflict() {
+    let left = TempDir::new().unwrap();
+    let right = TempDir::new().unwrap();
+    write_image(&left.path().join("a").join("x.png"), 0);
+    write_image(&left.path().join("b").join("x.png"), 0);
+
+    let mut config = DirDiffConfig::new(left.path().to_path_buf(), right.path().to_path_buf());
+    config.set_flatten(true);
+    assert!(matches!(
+        config.pairs(),
+        Err(kompari::Error::PairingConflict(_, _))
+    ));
+}
@@ -0,0 +1,88 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use kompari::color::Rgba8;
+use kompari::{MinImage, SnapshotConfig, SnapshotMode, SnapshotStatus, load_image};
+use tempfile::TempDir;
+
+fn image(color: Rgba8) -> MinImage {
+    MinImage {
+        width: 4,
+        height: 3,
+        data: vec![color; 12],
+    }
+}
+
+const WHITE: Rgba8 = Rgba8 {
+    r: 255,
+    g: 255,
+    b: 255,
+    a: 255,
+};
+const RED: Rgba8 = Rgba8 {
+    r: 255,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+
+fn config(dir: &TempDir, mode: SnapshotMode) -> SnapshotConfig {
+    let mut config = SnapshotConfig::in_dir(dir.path());
+    config.set_mode(Some(mode));
+    config
+}
+
+#[test]
+fn test_snapshot_modes() {
+    let dir = TempDir::new().unwrap();
+    let snapshot = dir.path().join("tests/snapshots/img.png");
+    let current = dir.path().join("tests/current/img.png");
+
+    let verify = config(&dir, SnapshotMode::Verify);
+    let status = verify.check(&image(WHITE), "img.png").unwrap();
+    assert!(matches!(status, SnapshotStatus::Missing));
+    assert!(current.is_file());
+    assert!(!snapshot.exists());
+
+    let new_only = config(&dir, SnapshotMode::NewOnly);
+    let status = new_only.check(&image(WHITE), "img.png").unwrap();
+    assert!(matches!(status, SnapshotStatus::Created));
+    assert_eq!(load_image(&snapshot).unwrap().data, image(WHITE).data);
+    let status = new_only.check(&image(RED), "img.png").unwrap();
+    assert!(matches!(status, SnapshotStatus::Different(_)));
+
+    std::fs::remove_file(&current).unwrap();
+    let generate_all = config(&dir, SnapshotMode::GenerateAll);
+    let status = generate_all.check(&image(WHITE), "img.png").unwrap();
+    assert!(matches!(status, SnapshotStatus::Match));
+    assert!(current.is_file());
+
+    let overwrite = config(&dir, SnapshotMode::Overwrite);
+    let status = overwrite.check(&image(RED), "img.png").unwrap();
+    assert!(matches!(status, SnapshotStatus::Updated));
+    assert_eq!(load_image(&snapshot).unwrap().data, image(RED).data);
+    verify.assert_snapshot(&image(RED), "img.png");
+}
+
+#[test]
+fn test_snapshot_panic_message() {
+    let dir = TempDir::new().unwrap();
+    config(&dir, SnapshotMode::Overwrite).assert_snapshot(&image(WHITE), "img.png");
+    let mut verify = config(&dir, SnapshotMode::Verify);
+    verify.set_report_command(Some("cargo xtask report".into()));
+    let panic =
+        std::panic::catch_unwind(|| verify.assert_snapshot(&image(RED), "img.png")).unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.contains("differs: 12 of 12 pixels (100.0%)"));
+    assert!(message.contains("Run `cargo xtask report`"));
+}
+
+#[test]
+fn test_snapshot_mode_parse() {
+    assert_eq!(SnapshotMode::parse("new-only"), Some(SnapshotMode::NewOnly));
+    assert_eq!(
+        SnapshotMode::parse("Generate-All"),
+        Some(SnapshotMode::GenerateAll)
+    );
+    assert_eq!(SnapshotMode::parse("bless"), None);
+}
@@ -13,17 +13,58 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 use clap::Parser;
-use kompari::DirDiffConfig;
-use kompari_html::{ReportConfig, render_html_report, start_review_server};
-use kompari_tasks::check_size_optimizations;
+use kompari::{
+    BlessJournal, DirDiffConfig, Manifest, MergeStatus, MultiDiffConfig, NameMapping, ThreeWayDiff,
+    ThreeWayDiffConfig, ThreeWayPairResult, compare_three_way_files,
+};
+use kompari_html::{
+    ReportConfig, render_html_report, render_markdown_report, render_multi_html_report,
+    render_three_way_html_report, start_review_server,
+};
+use kompari_tasks::args::{ReportArgs, ReportFormat};
+use kompari_tasks::{
+    CheckStatus, ERROR_EXIT_CODE, check_size_optimizations, render_json_report,
+    report_systemic_problems, run_bless, run_check, run_undo, show_diff, write_junit_report,
+};
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 #[derive(Parser, Debug)]
 pub struct CliReportArgs {
     #[clap(flatten)]
     diff_args: DiffArgs,
     #[clap(flatten)]
-    args: kompari_tasks::args::ReportArgs,
+    args: ReportArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct CliCheckArgs {
+    #[clap(flatten)]
+    diff_args: DiffArgs,
+    #[clap(flatten)]
+    args: kompari_tasks::args::CheckArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct CliBlessArgs {
+    #[clap(flatten)]
+    diff_args: DiffArgs,
+    #[clap(flatten)]
+    args: kompari_tasks::args::BlessArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct CliUndoArgs {
+    /// Directory with blessed images, or the manifest file listing them
+    left_path: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+pub struct CliShowArgs {
+    #[clap(flatten)]
+    diff_args: DiffArgs,
+    #[clap(flatten)]
+    args: kompari_tasks::args::ShowArgs,
 }
 
 #[derive(Parser, Debug)]
@@ -37,10 +78,22 @@ pub struct CliReviewArgs {
 #[derive(Parser, Debug, Clone)]
 struct DiffArgs {
     /// Path to "left" images
-    left_path: PathBuf,
+    #[arg(required_unless_present = "manifest", conflicts_with = "manifest")]
+    left_path: Option<PathBuf>,
 
     /// Path to "right" images
-    right_path: PathBuf,
+    #[arg(required_unless_present = "manifest", conflicts_with = "manifest")]
+    right_path: Option<PathBuf>,
+
+    /// Read left images from the given git revision (e.g. HEAD or a branch name) of the
+    /// repository containing the left path, instead of from the working tree
+    #[arg(long, conflicts_with = "manifest")]
+    left_revision: Option<String>,
+
+    /// Compare pairs listed in a manifest file (JSON or CSV with columns
+    /// `title,left,right[,tolerance]`) instead of two directories
+    #[arg(long)]
+    manifest: Option<PathBuf>,
 
     /// Left title
     #[arg(long, default_value = "Left image")]
@@ -58,9 +111,68 @@ struct DiffArgs {
     #[arg(long, default_value_t = false)]
     ignore_right_missing: bool,
 
-    /// Ignore match
+    /// Include matching images in the report (collapsed by default)
+    #[arg(long, default_value_t = false)]
+    include_matches: bool,
+
+    /// Filter filenames by name
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Only compare images whose name matches the glob (can be used multiple times)
+    #[arg(long)]
+    include: Vec<String>,
+
+    /// Skip images whose name matches the glob (can be used multiple times)
+    #[arg(long)]
+    exclude: Vec<String>,
+
+    /// Prefix stripped from left file names before pairing
+    #[arg(long, default_value = "")]
+    left_prefix: String,
+
+    /// Suffix stripped from left file stems before pairing (e.g. "_expected")
+    #[arg(long, default_value = "")]
+    left_suffix: String,
+
+    /// Prefix stripped from right file names before pairing
+    #[arg(long, default_value = "")]
+    right_prefix: String,
+
+    /// Suffix stripped from right file stems before pairing (e.g. "_actual")
+    #[arg(long, default_value = "")]
+    right_suffix: String,
+
+    /// Load Git LFS pointer files from the local LFS storage when their objects are present
+    #[arg(long, default_value_t = false)]
+    resolve_lfs: bool,
+
+    /// Pair files only by file name, ignoring subdirectories
     #[arg(long, default_value_t = false)]
-    ignore_match: bool,
+    flatten: bool,
+
+    /// Pair file names case-insensitively
+    #[arg(long, default_value_t = false)]
+    case_insensitive: bool,
+
+    /// Maximum per-pixel color distance to consider as matching (set to `0` to require exact
+    /// matches)
+    #[arg(long, default_value_t = 0)]
+    pixel_distance_tolerance: u8,
+}
+
+#[derive(Parser, Debug)]
+pub struct CliMultiReportArgs {
+    /// Path to reference images
+    reference_path: PathBuf,
+
+    /// Paths to candidate images; each directory is compared against the reference
+    #[arg(required = true)]
+    candidate_paths: Vec<PathBuf>,
+
+    /// Reference title
+    #[arg(long, default_value = "Reference")]
+    reference_title: String,
 
     /// Filter filenames by name
     #[arg(long)]
@@ -70,6 +182,55 @@ struct DiffArgs {
     /// matches)
     #[arg(long, default_value_t = 0)]
     pixel_distance_tolerance: u8,
+
+    #[clap(flatten)]
+    args: ReportArgs,
+}
+
+#[derive(Parser, Debug)]
+pub struct CliMergeReportArgs {
+    /// Path to images of the common ancestor
+    base_path: PathBuf,
+
+    /// Path to "our" images
+    ours_path: PathBuf,
+
+    /// Path to "their" images
+    theirs_path: PathBuf,
+
+    /// Filter filenames by name
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Maximum per-pixel color distance to consider as matching (set to `0` to require exact
+    /// matches)
+    #[arg(long, default_value_t = 0)]
+    pixel_distance_tolerance: u8,
+
+    #[clap(flatten)]
+    args: ReportArgs,
+}
+
+/// Arguments in the order in which git passes them to a merge driver (`%O %A %B`)
+#[derive(Parser, Debug)]
+pub struct CliMergeDriverArgs {
+    /// Common ancestor's version of the image
+    base: PathBuf,
+
+    /// Our version of the image; the merge result is written here
+    ours: PathBuf,
+
+    /// Their version of the image
+    theirs: PathBuf,
+
+    /// Maximum per-pixel color distance to consider as matching (set to `0` to require exact
+    /// matches)
+    #[arg(long, default_value_t = 0)]
+    pixel_distance_tolerance: u8,
+
+    /// Write an HTML report into the given file when there is a conflict
+    #[arg(long)]
+    report: Option<PathBuf>,
 }
 
 #[derive(Parser, Debug)]
@@ -85,45 +246,242 @@ pub struct CliSizeCheckArgs {
 pub enum Args {
     Report(CliReportArgs),
     Review(CliReviewArgs),
+    /// Compare several candidate directories against one reference directory
+    MultiReport(CliMultiReportArgs),
+    /// Three-way comparison of snapshot directories (base, ours, theirs)
+    MergeReport(CliMergeReportArgs),
+    /// Merge a snapshot image; usable as a git merge driver, exits with 1 on conflict
+    MergeDriver(CliMergeDriverArgs),
     SizeCheck(CliSizeCheckArgs),
+    /// Print a summary of differences with inline thumbnails on supported terminals
+    Show(CliShowArgs),
+    /// Compare images for CI; exits with 0 if all match, 1 on differences,
+    /// 2 on missing files, 3 on load errors and 4 on other errors
+    Check(CliCheckArgs),
+    /// Copy selected right images over left images
+    Bless(CliBlessArgs),
+    /// Undo the last bless operation (from `bless` or the review server)
+    Undo(CliUndoArgs),
 }
 
-fn make_diff_config(args: DiffArgs) -> (DirDiffConfig, ReportConfig) {
-    let mut diff_config = DirDiffConfig::new(args.left_path, args.right_path);
+fn make_diff_config(args: DiffArgs) -> kompari::Result<(DirDiffConfig, ReportConfig)> {
+    let mut diff_config = DirDiffConfig::new(
+        args.left_path.unwrap_or_default(),
+        args.right_path.unwrap_or_default(),
+    );
+    if let Some(manifest) = args.manifest {
+        diff_config.set_manifest(Some(Manifest::load(&manifest)?));
+    }
+    diff_config.set_left_revision(args.left_revision.as_deref())?;
     diff_config.set_ignore_left_missing(args.ignore_left_missing);
     diff_config.set_ignore_right_missing(args.ignore_right_missing);
+    diff_config.set_include_matches(args.include_matches);
     diff_config.set_filter_name(args.filter);
+    diff_config.set_include_globs(args.include);
+    diff_config.set_exclude_globs(args.exclude);
+    diff_config.set_left_name_mapping(NameMapping::affix(args.left_prefix, args.left_suffix));
+    diff_config.set_right_name_mapping(NameMapping::affix(args.right_prefix, args.right_suffix));
+    diff_config.set_resolve_lfs(args.resolve_lfs);
+    diff_config.set_flatten(args.flatten);
+    diff_config.set_case_insensitive(args.case_insensitive);
     diff_config.set_pixel_distance_tolerance(args.pixel_distance_tolerance);
 
     let mut report_config = ReportConfig::default();
-    report_config.set_left_title(args.left_title);
+    if let Some(revision) = args.left_revision {
+        report_config.set_left_title(format!("{} ({revision})", args.left_title));
+    } else {
+        report_config.set_left_title(args.left_title);
+    }
     report_config.set_right_title(args.right_title);
 
-    (diff_config, report_config)
+    Ok((diff_config, report_config))
 }
 
-fn main() -> kompari::Result<()> {
-    let args = Args::parse();
+/// Returns `false` if there is a conflict that has to be resolved manually.
+fn merge_driver(args: &CliMergeDriverArgs) -> kompari::Result<bool> {
+    let result = compare_three_way_files(
+        &args.base,
+        &args.ours,
+        &args.theirs,
+        args.pixel_distance_tolerance,
+    );
+    let status = match &result {
+        Ok(result) => result.status,
+        Err(e) => {
+            if let Some(e) = e.left() {
+                eprintln!("Cannot load '{}': {e}", args.ours.display());
+            }
+            if let Some(e) = e.right() {
+                eprintln!("Cannot load '{}': {e}", args.theirs.display());
+            }
+            MergeStatus::Conflict
+        }
+    };
+    match status {
+        MergeStatus::Unchanged | MergeStatus::ChangedInOurs | MergeStatus::BothChangedIdentical => {
+        }
+        MergeStatus::ChangedInTheirs => {
+            std::fs::copy(&args.theirs, &args.ours)?;
+        }
+        MergeStatus::Conflict => {
+            eprintln!("Images were changed differently on both sides");
+            if let Some(output) = &args.report {
+                let diff = ThreeWayDiff::from_results(vec![ThreeWayPairResult {
+                    title: args.ours.display().to_string(),
+                    base: args.base.clone(),
+                    ours: args.ours.clone(),
+                    theirs: args.theirs.clone(),
+                    result,
+                }]);
+                let mut report_config = ReportConfig::default();
+                report_config.set_embed_images(true);
+                let report = render_three_way_html_report(&report_config, &diff)?;
+                std::fs::write(output, report)?;
+                eprintln!("Report written into '{}'", output.display());
+            }
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Other formats are supported only for two-directory reports.
+fn require_html_format(args: &ReportArgs) -> kompari::Result<()> {
+    if args.format != ReportFormat::Html || args.junit.is_some() {
+        return Err(kompari::Error::GenericError(
+            "Only the `report` command supports JSON, Markdown and JUnit output".into(),
+        ));
+    }
+    Ok(())
+}
 
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::from(ERROR_EXIT_CODE)
+        }
+    }
+}
+
+/// Run the command and return the exit code.
+fn run(args: Args) -> kompari::Result<u8> {
     match args {
         Args::Report(args) => {
-            let (diff_config, mut report_config) = make_diff_config(args.diff_args);
+            let (diff_config, mut report_config) = make_diff_config(args.diff_args)?;
+            if report_systemic_problems(&diff_config)?.is_some() {
+                return Ok(CheckStatus::LoadErrors.exit_code());
+            }
             let diff = diff_config.create_diff()?;
+            // Images exported from a git revision are removed when the process exits
+            report_config
+                .set_embed_images(args.args.embed_images || diff_config.left_revision().is_some());
+            report_config.set_size_optimization(args.args.optimize_size.to_level());
+            let output = args.args.output.unwrap_or_else(|| {
+                PathBuf::from("report").with_extension(args.args.format.extension())
+            });
+            let report = match args.args.format {
+                ReportFormat::Html => render_html_report(&report_config, diff.results())?,
+                ReportFormat::Json => render_json_report(&diff)?,
+                ReportFormat::Markdown => render_markdown_report(
+                    &report_config,
+                    diff.results(),
+                    args.args.markdown_images.then_some(output.as_path()),
+                )?,
+            };
+            std::fs::write(&output, report)?;
+            println!("Report written into '{}'", output.display());
+            if let Some(junit) = &args.args.junit {
+                write_junit_report(&diff, junit)?;
+                println!("JUnit report written into '{}'", junit.display());
+            }
+            if args.args.exit_code {
+                return Ok(CheckStatus::of_results(diff.results()).exit_code());
+            }
+        }
+        Args::Review(args) => {
+            let (diff_config, mut report_config) = make_diff_config(args.diff_args)?;
+            if report_systemic_problems(&diff_config)?.is_some() {
+                return Ok(CheckStatus::LoadErrors.exit_code());
+            }
+            report_config.set_size_optimization(args.args.optimize_size.to_level());
+            let summary =
+                start_review_server(&diff_config, &report_config, &args.args.review_config())?;
+            if summary.n_unresolved() > 0 {
+                return Ok(CheckStatus::Differences.exit_code());
+            }
+        }
+        Args::MultiReport(args) => {
+            require_html_format(&args.args)?;
+            let mut multi_config = MultiDiffConfig::new(args.reference_path);
+            multi_config.diff_config().set_filter_name(args.filter);
+            multi_config
+                .diff_config()
+                .set_pixel_distance_tolerance(args.pixel_distance_tolerance);
+            for path in args.candidate_paths {
+                let name = path
+                    .file_name()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+                    .to_string();
+                multi_config.add_candidate(name, path);
+            }
+            let diff = multi_config.create_diff()?;
+            let mut report_config = ReportConfig::default();
+            report_config.set_left_title(args.reference_title);
             report_config.set_embed_images(args.args.embed_images);
             report_config.set_size_optimization(args.args.optimize_size.to_level());
-            let report = render_html_report(&report_config, diff.results())?;
+            let report = render_multi_html_report(&report_config, &diff)?;
             let output = args.args.output.unwrap_or("report.html".into());
             std::fs::write(&output, report)?;
             println!("Report written into '{}'", output.display());
         }
-        Args::Review(args) => {
-            let (diff_config, mut report_config) = make_diff_config(args.diff_args);
+        Args::MergeReport(args) => {
+            require_html_format(&args.args)?;
+            let mut diff_config =
+                ThreeWayDiffConfig::new(args.base_path, args.ours_path, args.theirs_path);
+            diff_config.set_filter_name(args.filter);
+            diff_config.set_pixel_distance_tolerance(args.pixel_distance_tolerance);
+            let diff = diff_config.create_diff()?;
+            let mut report_config = ReportConfig::default();
+            report_config.set_embed_images(args.args.embed_images);
             report_config.set_size_optimization(args.args.optimize_size.to_level());
-            start_review_server(&diff_config, &report_config, args.args.port)?;
+            let report = render_three_way_html_report(&report_config, &diff)?;
+            let output = args.args.output.unwrap_or("report.html".into());
+            std::fs::write(&output, report)?;
+            println!("Report written into '{}'", output.display());
+        }
+        Args::MergeDriver(args) => {
+            if !merge_driver(&args)? {
+                return Ok(1);
+            }
         }
         Args::SizeCheck(args) => {
             check_size_optimizations(&args.path, &args.args)?;
         }
+        Args::Check(args) => {
+            let (diff_config, report_config) = make_diff_config(args.diff_args)?;
+            if report_systemic_problems(&diff_config)?.is_some() {
+                return Ok(CheckStatus::LoadErrors.exit_code());
+            }
+            let status = run_check(&diff_config, &report_config, &args.args)?;
+            return Ok(status.exit_code());
+        }
+        Args::Bless(args) => {
+            let (diff_config, _) = make_diff_config(args.diff_args)?;
+            run_bless(&diff_config, &args.args)?;
+        }
+        Args::Undo(args) => {
+            run_undo(&BlessJournal::for_target_dir(&args.left_path))?;
+        }
+        Args::Show(args) => {
+            let (diff_config, _) = make_diff_config(args.diff_args)?;
+            if report_systemic_problems(&diff_config)?.is_some() {
+                return Ok(CheckStatus::LoadErrors.exit_code());
+            }
+            show_diff(&diff_config, &args.args)?;
+        }
     }
-    Ok(())
+    Ok(0)
 }
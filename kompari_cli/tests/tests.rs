@@ -41,6 +41,9 @@ fn test_create_report() {
         assert!(report.contains(&format!("{}.png", name)));
     }
     assert!(!report.contains("same.png"));
+    assert!(report.contains(r#"id="toolbar""#));
+    assert!(report.contains(r#"data-title="size_error.png" data-status="size-mismatch""#));
+    assert!(report.contains(r#"data-title="left_missing.png" data-status="missing-left""#));
 }
 
 #[test]
@@ -68,3 +71,674 @@ fn test_filter_filenames() {
         assert!(!report.contains(&format!("{}.png", name)));
     }
 }
+
+#[test]
+fn test_manifest() {
+    let workdir = TempDir::new().unwrap();
+    let test_dir = test_assets_dir();
+    let manifest = workdir.path().join("pairs.csv");
+    std::fs::write(
+        &manifest,
+        format!(
+            "title,left,right\nmy_pair,{},{}\n",
+            test_dir.join("left/shift.png").display(),
+            test_dir.join("right/shift.png").display()
+        ),
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report").arg("--manifest").arg(&manifest);
+    cmd.current_dir(&workdir);
+    cmd.assert().success();
+    let report = std::fs::read_to_string(workdir.path().join("report.html")).unwrap();
+    assert!(report.contains("my_pair"));
+    assert!(!report.contains("bright.png"));
+}
+
+#[test]
+fn test_multi_report() {
+    let workdir = TempDir::new().unwrap();
+    let test_dir = test_assets_dir();
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("multi-report")
+        .arg(test_dir.join("left"))
+        .arg(test_dir.join("right"))
+        .arg(test_dir.join("left"));
+    cmd.current_dir(&workdir);
+    cmd.assert().success();
+    let report = std::fs::read_to_string(workdir.path().join("report.html")).unwrap();
+    assert!(report.contains("shift.png"));
+    assert!(report.contains("Match"));
+    assert!(!report.contains("same.png"));
+}
+
+#[test]
+fn test_merge_driver() {
+    let workdir = TempDir::new().unwrap();
+    let test_dir = test_assets_dir();
+    let base = workdir.path().join("base.png");
+    let ours = workdir.path().join("ours.png");
+    let theirs = workdir.path().join("theirs.png");
+    let report = workdir.path().join("conflict.html");
+    let run = || {
+        let mut cmd = Command::cargo_bin("kompari").unwrap();
+        cmd.arg("merge-driver")
+            .arg(&base)
+            .arg(&ours)
+            .arg(&theirs)
+            .arg("--report")
+            .arg(&report);
+        cmd.assert()
+    };
+
+    // Only "theirs" changed, so it is taken
+    std::fs::copy(test_dir.join("left/shift.png"), &base).unwrap();
+    std::fs::copy(test_dir.join("left/shift.png"), &ours).unwrap();
+    std::fs::copy(test_dir.join("right/shift.png"), &theirs).unwrap();
+    run().success();
+    assert_eq!(
+        std::fs::read(&ours).unwrap(),
+        std::fs::read(&theirs).unwrap()
+    );
+    assert!(!report.exists());
+
+    // Both changed differently
+    std::fs::copy(test_dir.join("right/bright.png"), &ours).unwrap();
+    run().code(1);
+    assert_eq!(
+        std::fs::read(&ours).unwrap(),
+        std::fs::read(test_dir.join("right/bright.png")).unwrap()
+    );
+    let report = std::fs::read_to_string(&report).unwrap();
+    assert!(report.contains("Conflict"));
+}
+
+#[test]
+fn test_lfs_pointers_diagnosis() {
+    let workdir = TempDir::new().unwrap();
+    let left = workdir.path().join("left");
+    let right = workdir.path().join("right");
+    std::fs::create_dir(&left).unwrap();
+    std::fs::create_dir(&right).unwrap();
+    for name in ["a.png", "b.png", "c.png"] {
+        std::fs::write(
+            left.join(name),
+            "version https://git-lfs.github.com/spec/v1\n\
+             oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+             size 12345\n",
+        )
+        .unwrap();
+        std::fs::copy(test_assets_dir().join("left/same.png"), right.join(name)).unwrap();
+    }
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report").arg(&left).arg(&right);
+    cmd.current_dir(&workdir);
+    let output = cmd.assert().code(3).get_output().clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("3 of 6 images are unresolved Git LFS pointers"));
+    assert!(!workdir.path().join("report.html").exists());
+}
+
+#[test]
+fn test_truncated_png_diagnosis() {
+    let workdir = TempDir::new().unwrap();
+    let (left, right) = copy_test_dirs(&workdir);
+    for entry in std::fs::read_dir(&right).unwrap() {
+        let path = entry.unwrap().path();
+        let data = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &data[..data.len() / 2]).unwrap();
+    }
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("check").arg(&left).arg(&right);
+    let output = cmd.assert().code(3).get_output().clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("decoded images are corrupted PNG images"));
+}
+
+#[test]
+fn test_json_report() {
+    let workdir = TempDir::new().unwrap();
+    let test_dir = test_assets_dir();
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report").arg("--format").arg("json");
+    cmd.arg(test_dir.join("left")).arg(test_dir.join("right"));
+    cmd.current_dir(&workdir);
+    cmd.assert().success();
+    let report = std::fs::read_to_string(workdir.path().join("report.json")).unwrap();
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    let diffs = report["diffs"].as_array().unwrap();
+    assert_eq!(diffs.len(), 7);
+    let bright = &diffs[0];
+    assert_eq!(bright["title"], "bright.png");
+    assert_eq!(bright["image_diff"]["Ok"]["type"], "Content");
+    assert_eq!(bright["image_diff"]["Ok"]["n_different_pixels"], 18623);
+    let missing = diffs
+        .iter()
+        .find(|d| d["title"] == "left_missing.png")
+        .unwrap();
+    assert_eq!(missing["image_diff"]["Err"]["Left"]["kind"], "FileNotFound");
+}
+
+#[test]
+fn test_junit_report() {
+    let workdir = TempDir::new().unwrap();
+    let test_dir = test_assets_dir();
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report").arg("--junit").arg("junit.xml");
+    cmd.arg(test_dir.join("left")).arg(test_dir.join("right"));
+    cmd.current_dir(&workdir);
+    cmd.assert().success();
+    let report = std::fs::read_to_string(workdir.path().join("junit.xml")).unwrap();
+    assert!(report.contains(r#"<testsuite name="kompari" tests="7" failures="7" errors="0">"#));
+    assert!(report.contains("Different pixels: 18623 of 18634"));
+    assert!(report.contains("Left image is missing"));
+    let image = "junit-images/0000-bright_png-RedGreen.png";
+    assert!(report.contains(&format!("[[ATTACHMENT|{image}]]")));
+    assert!(workdir.path().join(image).is_file());
+
+    // Both images of a manifest entry can be missing
+    let manifest = workdir.path().join("pairs.csv");
+    std::fs::write(
+        &manifest,
+        format!(
+            "title,left,right\nmy_pair,{},{}\nno_pair,none.png,none.png\n",
+            test_dir.join("left/bright.png").display(),
+            test_dir.join("right/bright.png").display()
+        ),
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report").arg("--junit").arg("junit.xml");
+    cmd.arg("--manifest").arg(&manifest);
+    cmd.current_dir(&workdir);
+    cmd.assert().success();
+    let report = std::fs::read_to_string(workdir.path().join("junit.xml")).unwrap();
+    assert!(report.contains(r#"tests="2" failures="2" errors="0""#));
+    assert!(report.contains("Both images are missing"));
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("show").arg("--graphics").arg("none");
+    cmd.arg("--manifest").arg(&manifest);
+    let output = cmd.assert().success().get_output().clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("both images"));
+    assert!(!stdout.contains("Loading error"));
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("check").arg("--manifest").arg(&manifest);
+    cmd.current_dir(&workdir);
+    cmd.assert().code(2);
+}
+
+#[test]
+fn test_markdown_report() {
+    let workdir = TempDir::new().unwrap();
+    let test_dir = test_assets_dir();
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report").arg("--format").arg("markdown");
+    cmd.arg("--markdown-images");
+    cmd.arg(test_dir.join("left")).arg(test_dir.join("right"));
+    cmd.current_dir(&workdir);
+    cmd.assert().success();
+    let report = std::fs::read_to_string(workdir.path().join("report.md")).unwrap();
+    assert!(report.contains("| bright.png | Different | 18623 (99.9%) | 200x150 |"));
+    assert!(report.contains("| size_error.png | Size mismatch |  | 850x88 → 147x881 |"));
+    assert!(report.contains("(report-images/0000.png)"));
+    assert!(workdir.path().join("report-images/0000.png").is_file());
+
+    // Matches are not listed as failures
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report").arg("--format").arg("markdown");
+    cmd.arg("--include-matches").arg("--filter").arg("same");
+    cmd.arg(test_dir.join("left")).arg(test_dir.join("right"));
+    cmd.current_dir(&workdir);
+    cmd.assert().success();
+    let report = std::fs::read_to_string(workdir.path().join("report.md")).unwrap();
+    assert_eq!(report, "**Kompari:** all images match\n");
+}
+
+#[test]
+fn test_show() {
+    let test_dir = test_assets_dir();
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("show").arg("--graphics").arg("none");
+    cmd.arg(test_dir.join("left")).arg(test_dir.join("right"));
+    let output = cmd.assert().success().get_output().clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("18623 pixels (99.9%)"));
+    assert!(stdout.contains("7 image(s) differ"));
+    assert!(!stdout.contains("\x1bP"));
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("show").arg("--graphics").arg("sixel");
+    cmd.arg("--filter").arg("bright");
+    cmd.arg(test_dir.join("left")).arg(test_dir.join("right"));
+    let output = cmd.assert().success().get_output().clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\x1bP0;1q\"1;1;400;96"));
+}
+
+#[test]
+fn test_check() {
+    let workdir = TempDir::new().unwrap();
+    let test_dir = test_assets_dir();
+    let left = test_dir.join("left");
+    let right = test_dir.join("right");
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("check").arg("--filter").arg("same");
+    cmd.arg(&left).arg(&right);
+    cmd.current_dir(&workdir);
+    cmd.assert().code(0);
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("check").arg("--filter").arg("bright");
+    cmd.arg("--report-on-failure").arg("failure.html");
+    cmd.arg(&left).arg(&right);
+    cmd.current_dir(&workdir);
+    cmd.assert().code(1);
+    assert!(workdir.path().join("failure.html").is_file());
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("check").arg(&left).arg(&right);
+    let output = cmd.assert().code(2).get_output().clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Missing: "));
+
+    // Errors have their own exit code
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("check").arg("--include").arg("[");
+    cmd.arg(&left).arg(&right);
+    cmd.assert().code(4);
+
+    // Reports exit with the same codes only when asked to
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report").arg("--filter").arg("bright");
+    cmd.arg(&left).arg(&right);
+    cmd.current_dir(&workdir);
+    cmd.assert().code(0);
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report")
+        .arg("--exit-code")
+        .arg("--filter")
+        .arg("bright");
+    cmd.arg(&left).arg(&right);
+    cmd.current_dir(&workdir);
+    cmd.assert().code(1);
+    assert!(workdir.path().join("report.html").is_file());
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report")
+        .arg("--exit-code")
+        .arg("--filter")
+        .arg("same");
+    cmd.arg(&left).arg(&right);
+    cmd.current_dir(&workdir);
+    cmd.assert().code(0);
+}
+
+#[test]
+fn test_include_matches() {
+    let workdir = TempDir::new().unwrap();
+    let test_dir = test_assets_dir();
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report").arg("--include-matches");
+    cmd.arg(test_dir.join("left")).arg(test_dir.join("right"));
+    cmd.current_dir(&workdir);
+    cmd.assert().success();
+    let report = std::fs::read_to_string(workdir.path().join("report.html")).unwrap();
+    assert!(report.contains("same.png"));
+    assert!(report.contains(
+        r#"<details class="diff-entry match-entry" data-title="same.png" data-status="match""#
+    ));
+}
+
+fn copy_test_dirs(workdir: &TempDir) -> (PathBuf, PathBuf) {
+    let mut result = Vec::new();
+    for name in ["left", "right"] {
+        let target = workdir.path().join(name);
+        std::fs::create_dir(&target).unwrap();
+        for entry in std::fs::read_dir(test_assets_dir().join(name)).unwrap() {
+            let entry = entry.unwrap();
+            std::fs::copy(entry.path(), target.join(entry.file_name())).unwrap();
+        }
+        result.push(target);
+    }
+    (result[0].clone(), result[1].clone())
+}
+
+#[test]
+fn test_bless() {
+    let workdir = TempDir::new().unwrap();
+    let (left, right) = copy_test_dirs(&workdir);
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("bless").arg("--all").arg("--dry-run");
+    cmd.arg(&left).arg(&right);
+    let output = cmd.assert().success().get_output().clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("6 image(s) would be blessed"));
+    assert!(!left.join("left_missing.png").exists());
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("bless").arg("--status").arg("new");
+    cmd.arg("--name").arg("*.png");
+    cmd.arg(&left).arg(&right);
+    cmd.assert().success();
+    assert!(left.join("left_missing.png").exists());
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("bless").arg("--name").arg("bright*");
+    cmd.arg(&left).arg(&right);
+    cmd.assert().success();
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("check").arg("--filter").arg("bright");
+    cmd.arg(&left).arg(&right);
+    cmd.assert().code(0);
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("bless").arg(&left).arg(&right);
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_bless_left_revision() {
+    let workdir = TempDir::new().unwrap();
+    let (left, right) = copy_test_dirs(&workdir);
+    for args in [
+        &["init", "-q"][..],
+        &["add", "."],
+        &["commit", "-q", "-m", "Snapshots"],
+    ] {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(workdir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    // Blessed images would be written into the export of the revision
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("bless")
+        .arg("--all")
+        .arg("--left-revision")
+        .arg("HEAD");
+    cmd.arg(&left).arg(&right);
+    let output = cmd.assert().failure().get_output().clone();
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("git revision `HEAD`")
+    );
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("bless").arg("--all").arg("--dry-run");
+    cmd.arg("--left-revision").arg("HEAD");
+    cmd.arg(&left).arg(&right);
+    cmd.assert().success();
+
+    // The export of the revision is removed on exit, so its images are embedded
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("report").arg("--left-revision").arg("HEAD");
+    cmd.arg(&left).arg(&right);
+    cmd.current_dir(&workdir);
+    cmd.assert().success();
+    let report = std::fs::read_to_string(workdir.path().join("report.html")).unwrap();
+    assert!(report.contains("data:image/png;base64,"));
+    assert!(!report.contains("kompari-git-"));
+}
+
+#[test]
+fn test_undo() {
+    let workdir = TempDir::new().unwrap();
+    let (left, right) = copy_test_dirs(&workdir);
+    let original = std::fs::read(left.join("bright.png")).unwrap();
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("bless").arg("--all");
+    cmd.arg(&left).arg(&right);
+    cmd.assert().success();
+    assert_ne!(std::fs::read(left.join("bright.png")).unwrap(), original);
+
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("undo").arg(&left);
+    cmd.assert().success();
+    assert_eq!(std::fs::read(left.join("bright.png")).unwrap(), original);
+    assert!(!left.join("left_missing.png").exists());
+}
+
+/// Review server started by the CLI; it is killed when dropped.
+struct ReviewProcess {
+    child: std::process::Child,
+    port: u16,
+    token: String,
+}
+
+impl ReviewProcess {
+    fn start(left: &Path, right: &Path) -> Self {
+        Self::start_with_args(
+            left.parent().unwrap(),
+            &[left.as_os_str(), right.as_os_str()],
+        )
+    }
+
+    fn start_with_args(workdir: &Path, args: &[&std::ffi::OsStr]) -> Self {
+        use std::io::BufRead;
+        let mut server = Self {
+            child: std::process::Command::new(env!("CARGO_BIN_EXE_kompari"))
+                .arg("review")
+                .arg("--port")
+                .arg("0")
+                .args(args)
+                .current_dir(workdir)
+                .stdout(std::process::Stdio::piped())
+                .spawn()
+                .unwrap(),
+            port: 0,
+            token: String::new(),
+        };
+        let mut lines = std::io::BufReader::new(server.child.stdout.take().unwrap()).lines();
+        for line in lines.by_ref() {
+            let line = line.unwrap();
+            // Running at http://127.0.0.1:<port>/?token=<token>
+            if let Some(url) = line.strip_prefix("Running at http://127.0.0.1:") {
+                let (port, token) = url.split_once("/?token=").unwrap();
+                server.port = port.parse().unwrap();
+                server.token = token.to_string();
+                // Keep reading, so the server does not fail on a closed pipe
+                std::thread::spawn(move || lines.for_each(drop));
+                return server;
+            }
+        }
+        panic!("Review server did not start");
+    }
+
+    /// Send a request and return the status code and the body of the response.
+    fn request(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> (u16, String) {
+        use std::io::{Read, Write};
+        let mut stream = std::net::TcpStream::connect(("127.0.0.1", self.port)).unwrap();
+        let mut request = format!(
+            "{method} {path} HTTP/1.1\r\nHost: localhost:{}\r\nConnection: close\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n",
+            self.port,
+            body.len()
+        );
+        for (name, value) in headers {
+            request.push_str(&format!("{name}: {value}\r\n"));
+        }
+        request.push_str("\r\n");
+        request.push_str(body);
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = response
+            .split_once("\r\n\r\n")
+            .map(|(_, body)| body.to_string())
+            .unwrap_or_default();
+        (status, body)
+    }
+
+    fn session_token(&self) -> String {
+        self.token.clone()
+    }
+
+    /// Finish the review and return the exit code of the server.
+    fn finish(&mut self) -> i32 {
+        let token = self.session_token();
+        let (status, _) = self.request("POST", "/finish", &[("X-Kompari-Token", &token)], "");
+        assert_eq!(status, 200, "Finishing the review failed");
+        self.child.wait().unwrap().code().unwrap()
+    }
+}
+
+impl Drop for ReviewProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_review_rejects_unknown_names() {
+    let workdir = TempDir::new().unwrap();
+    let (left, right) = copy_test_dirs(&workdir);
+    let mut server = ReviewProcess::start(&left, &right);
+    let token = server.session_token();
+    let token_header = [("X-Kompari-Token", token.as_str())];
+
+    let (status, _) = server.request(
+        "POST",
+        "/update",
+        &[],
+        r#"{"accepted_names": ["bright.png"]}"#,
+    );
+    assert_eq!(status, 403);
+
+    let outside = workdir.path().join("outside.png");
+    let names = [
+        "../outside.png".to_string(),
+        "../../outside.png".to_string(),
+        "bright.png/../../outside.png".to_string(),
+        outside.display().to_string(),
+        "same.png".to_string(),
+    ];
+    let body = serde_json::json!({ "accepted_names": names }).to_string();
+    let (status, response) = server.request("POST", "/update", &token_header, &body);
+    assert_eq!(status, 400);
+    let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(
+        response["rejected"].as_array().unwrap().len(),
+        names.len(),
+        "Unexpected response {response}"
+    );
+    assert!(!outside.exists());
+
+    // One unknown name rejects the whole request
+    let body = r#"{"accepted_names": ["bright.png", "../right/bright.png"]}"#;
+    let (status, response) = server.request("POST", "/update", &token_header, body);
+    assert_eq!(status, 400);
+    assert!(response.contains("../right/bright.png"));
+    assert!(!response.contains(r#""bright.png""#));
+
+    let body = r#"{"name": "../outside.png", "decision": "rejected"}"#;
+    let (status, _) = server.request("POST", "/review", &token_header, body);
+    assert_eq!(status, 400);
+
+    let body = r#"{"accepted_names": ["bright.png"]}"#;
+    let (status, _) = server.request("POST", "/update", &token_header, body);
+    assert_eq!(status, 200);
+    assert_eq!(
+        kompari::load_image(&left.join("bright.png")).unwrap().data,
+        kompari::load_image(&right.join("bright.png")).unwrap().data
+    );
+    // Other cases were not resolved
+    assert_eq!(server.finish(), 1);
+}
+
+#[test]
+fn test_review_manifest() {
+    let workdir = TempDir::new().unwrap();
+    let (left, right) = copy_test_dirs(&workdir);
+    let manifest = workdir.path().join("pairs.csv");
+    std::fs::write(
+        &manifest,
+        format!(
+            "title,left,right\nmy_pair,{},{}\n",
+            left.join("bright.png").display(),
+            right.join("bright.png").display()
+        ),
+    )
+    .unwrap();
+    // Without a left directory, the journal and the review state are stored next to
+    // the manifest, not in the working directory
+    let cwd = workdir.path().join("cwd");
+    std::fs::create_dir(&cwd).unwrap();
+    let mut server =
+        ReviewProcess::start_with_args(&cwd, &["--manifest".as_ref(), manifest.as_os_str()]);
+    let token = server.session_token();
+    let token_header = [("X-Kompari-Token", token.as_str())];
+
+    let body = r#"{"accepted_names": ["bright.png"]}"#;
+    let (status, _) = server.request("POST", "/update", &token_header, body);
+    assert_eq!(status, 400);
+
+    let body = r#"{"name": "my_pair", "decision": "follow-up", "comment": "Gamma"}"#;
+    let (status, _) = server.request("POST", "/review", &token_header, body);
+    assert_eq!(status, 200);
+
+    let accept = r#"{"accepted_names": ["my_pair"]}"#;
+    let (status, _) = server.request("POST", "/update", &token_header, accept);
+    assert_eq!(status, 200);
+    assert_eq!(
+        kompari::load_image(&left.join("bright.png")).unwrap().data,
+        kompari::load_image(&right.join("bright.png")).unwrap().data
+    );
+    let (_, reviews) = server.request("GET", "/review", &token_header, "");
+    let reviews: serde_json::Value = serde_json::from_str(&reviews).unwrap();
+    assert_eq!(reviews["my_pair"]["decision"], "pending");
+    assert_eq!(reviews["my_pair"]["comment"], "Gamma");
+
+    // Undo restores the decision together with the image
+    let (status, _) = server.request("POST", "/undo", &token_header, "");
+    assert_eq!(status, 200);
+    let (_, reviews) = server.request("GET", "/review", &token_header, "");
+    let reviews: serde_json::Value = serde_json::from_str(&reviews).unwrap();
+    assert_eq!(reviews["my_pair"]["decision"], "follow-up");
+
+    let (status, _) = server.request("POST", "/update", &token_header, accept);
+    assert_eq!(status, 200);
+    assert_eq!(server.finish(), 0);
+    let journal_dir = workdir.path().join(".pairs.csv.kompari-journal");
+    assert!(journal_dir.join("review.json").is_file());
+    assert_eq!(std::fs::read_dir(&cwd).unwrap().count(), 0);
+
+    // Blessing and undo from the command line use the same journal
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("undo").arg(&manifest).current_dir(&cwd);
+    cmd.assert().success();
+    assert_ne!(
+        kompari::load_image(&left.join("bright.png")).unwrap().data,
+        kompari::load_image(&right.join("bright.png")).unwrap().data
+    );
+    let mut cmd = Command::cargo_bin("kompari").unwrap();
+    cmd.arg("bless")
+        .arg("--all")
+        .arg("--manifest")
+        .arg(&manifest);
+    cmd.current_dir(&cwd).assert().success();
+    assert_eq!(
+        kompari::load_image(&left.join("bright.png")).unwrap().data,
+        kompari::load_image(&right.join("bright.png")).unwrap().data
+    );
+    assert_eq!(std::fs::read_dir(&cwd).unwrap().count(), 0);
+}
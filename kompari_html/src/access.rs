@@ -0,0 +1,327 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use axum::extract::Request;
+use axum::http::{HeaderMap, Method, StatusCode, header};
+use std::fmt::Write;
+use std::net::IpAddr;
+
+/// Header with the session token required by requests that change files or the review state.
+pub(crate) const TOKEN_HEADER: &str = "x-kompari-token";
+
+/// Query parameter with the session token, used in the URL printed on startup.
+const TOKEN_PARAM: &str = "token";
+
+/// Random token identifying clients of this run of the server.
+///
+/// Every request has to contain the token: pages opened from the URL printed on startup
+/// get it in a cookie, and requests that are not `GET` also have to send it in [`TOKEN_HEADER`],
+/// so other sites cannot make them.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionToken {
+    token: String,
+    /// Cookies are shared between ports of a host, so the name contains the port of the server.
+    cookie_name: String,
+}
+
+/// Access granted to a request.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Access {
+    Granted,
+    /// The token was given in the query; the client should store it in a cookie.
+    GrantedByQuery,
+}
+
+impl SessionToken {
+    pub(crate) fn new(port: u16) -> kompari::Result<Self> {
+        let mut bytes = [0_u8; 16];
+        getrandom::fill(&mut bytes).map_err(|e| {
+            kompari::Error::GenericError(format!("Cannot generate session token: {e}").into())
+        })?;
+        let mut token = String::with_capacity(bytes.len() * 2);
+        for byte in bytes {
+            write!(token, "{byte:02x}").unwrap();
+        }
+        Ok(Self {
+            token,
+            cookie_name: format!("kompari-token-{port}"),
+        })
+    }
+
+    pub(crate) fn value(&self) -> &str {
+        &self.token
+    }
+
+    /// Query string that grants access, e.g. `?token=...`.
+    pub(crate) fn query(&self) -> String {
+        format!("?{TOKEN_PARAM}={}", self.token)
+    }
+
+    /// Value of the `Set-Cookie` header storing the token.
+    pub(crate) fn set_cookie(&self) -> String {
+        format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Strict",
+            self.cookie_name, self.token
+        )
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        constant_time_eq(value.as_bytes(), self.token.as_bytes())
+    }
+
+    fn cookie_token<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .find_map(|cookie| {
+                cookie
+                    .trim()
+                    .strip_prefix(self.cookie_name.as_str())?
+                    .strip_prefix('=')
+            })
+    }
+
+    /// Check the `Host` header and the token of every request, and the `Origin` header
+    /// and the read-only mode for requests that are not `GET`.
+    pub(crate) fn check_request(
+        &self,
+        request: &Request,
+        read_only: bool,
+    ) -> Result<Access, (StatusCode, &'static str)> {
+        let headers: &HeaderMap = request.headers();
+        let host = headers
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .filter(|host| is_allowed_host(host))
+            .ok_or((StatusCode::FORBIDDEN, "Invalid Host header"))?;
+        let header_token = headers
+            .get(TOKEN_HEADER)
+            .and_then(|value| value.to_str().ok());
+        if matches!(*request.method(), Method::GET | Method::HEAD) {
+            if header_token
+                .or_else(|| self.cookie_token(headers))
+                .is_some_and(|token| self.matches(token))
+            {
+                return Ok(Access::Granted);
+            }
+            if token_from_query(request).is_some_and(|t| self.matches(t)) {
+                return Ok(Access::GrantedByQuery);
+            }
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Invalid session token, open the URL printed by the review server",
+            ));
+        }
+        if read_only {
+            return Err((StatusCode::FORBIDDEN, "The review server is read-only"));
+        }
+        if let Some(origin) = headers.get(header::ORIGIN) {
+            if origin.as_bytes() != format!("http://{host}").as_bytes() {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    "Cross-origin requests are not allowed",
+                ));
+            }
+        }
+        match header_token {
+            Some(token) if self.matches(token) => Ok(Access::Granted),
+            _ => Err((StatusCode::FORBIDDEN, "Invalid session token")),
+        }
+    }
+}
+
+fn token_from_query(request: &Request) -> Option<&str> {
+    request
+        .uri()
+        .query()?
+        .split('&')
+        .find_map(|param| param.strip_prefix(TOKEN_PARAM)?.strip_prefix('='))
+}
+
+/// Host name without the port; IPv6 brackets are kept.
+fn strip_port(host: &str) -> &str {
+    if let Some(end) = host.strip_prefix('[').and_then(|h| h.find(']')) {
+        return &host[..end + 2];
+    }
+    host.rsplit_once(':').map_or(host, |(name, _)| name)
+}
+
+/// Only `localhost` and IP addresses are accepted, so pages of other domains
+/// cannot reach the server through DNS rebinding.
+fn is_allowed_host(host: &str) -> bool {
+    let name = strip_port(host);
+    name.eq_ignore_ascii_case("localhost")
+        || name
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn token() -> SessionToken {
+        SessionToken {
+            token: "secret".to_string(),
+            cookie_name: "kompari-token-7200".to_string(),
+        }
+    }
+
+    fn request(method: &str, uri: &str, headers: &[(&str, &str)]) -> Request {
+        let mut builder = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    fn check(method: &str, uri: &str, headers: &[(&str, &str)]) -> Result<Access, StatusCode> {
+        token()
+            .check_request(&request(method, uri, headers), false)
+            .map_err(|(status, _)| status)
+    }
+
+    #[test]
+    fn test_hosts() {
+        assert_eq!(strip_port("localhost:7200"), "localhost");
+        assert_eq!(strip_port("[::1]:7200"), "[::1]");
+        assert_eq!(strip_port("[::1]"), "[::1]");
+        assert!(is_allowed_host("localhost:7200"));
+        assert!(is_allowed_host("LOCALHOST"));
+        assert!(is_allowed_host("127.0.0.1:7200"));
+        assert!(is_allowed_host("[::1]:7200"));
+        assert!(is_allowed_host("[fe80::1]"));
+        assert!(!is_allowed_host("example.com:7200"));
+        assert!(!is_allowed_host("localhost.example.com"));
+        assert!(!is_allowed_host("[::1"));
+
+        let host = [("Host", "example.com:7200"), (TOKEN_HEADER, "secret")];
+        assert_eq!(check("GET", "/", &host), Err(StatusCode::FORBIDDEN));
+        assert_eq!(check("GET", "/", &[]), Err(StatusCode::FORBIDDEN));
+        let host = [("Host", "[::1]:7200"), (TOKEN_HEADER, "secret")];
+        assert_eq!(check("GET", "/", &host), Ok(Access::Granted));
+    }
+
+    #[test]
+    fn test_get_token() {
+        let host = ("Host", "localhost:7200");
+        assert_eq!(check("GET", "/", &[host]), Err(StatusCode::FORBIDDEN));
+        assert_eq!(
+            check("GET", "/image/left/a.png", &[host]),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            check("GET", "/?token=wrong", &[host]),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            check("GET", "/?token=secret", &[host]),
+            Ok(Access::GrantedByQuery)
+        );
+        assert_eq!(
+            check(
+                "GET",
+                "/events",
+                &[host, ("Cookie", "a=b; kompari-token-7200=secret")]
+            ),
+            Ok(Access::Granted)
+        );
+        // Cookie of a server on another port
+        assert_eq!(
+            check("GET", "/", &[host, ("Cookie", "kompari-token-7201=secret")]),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            check("GET", "/", &[host, ("Cookie", "kompari-token-7200=wrong")]),
+            Err(StatusCode::FORBIDDEN)
+        );
+    }
+
+    #[test]
+    fn test_post_token() {
+        let host = ("Host", "localhost:7200");
+        assert_eq!(
+            check("POST", "/update", &[host]),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            check("POST", "/update", &[host, (TOKEN_HEADER, "wrong")]),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            check("POST", "/update", &[host, (TOKEN_HEADER, "secre")]),
+            Err(StatusCode::FORBIDDEN)
+        );
+        // A cookie or the query is not enough, other sites could make such requests
+        assert_eq!(
+            check(
+                "POST",
+                "/update",
+                &[host, ("Cookie", "kompari-token-7200=secret")]
+            ),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            check("POST", "/update?token=secret", &[host]),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            check("POST", "/update", &[host, (TOKEN_HEADER, "secret")]),
+            Ok(Access::Granted)
+        );
+    }
+
+    #[test]
+    fn test_origin() {
+        let host = ("Host", "localhost:7200");
+        let token = (TOKEN_HEADER, "secret");
+        assert_eq!(
+            check(
+                "POST",
+                "/update",
+                &[host, token, ("Origin", "http://localhost:7200")]
+            ),
+            Ok(Access::Granted)
+        );
+        assert_eq!(
+            check(
+                "POST",
+                "/update",
+                &[host, token, ("Origin", "http://evil.com")]
+            ),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            check(
+                "POST",
+                "/update",
+                &[host, token, ("Origin", "http://localhost:7201")]
+            ),
+            Err(StatusCode::FORBIDDEN)
+        );
+    }
+
+    #[test]
+    fn test_read_only() {
+        let headers = [("Host", "localhost:7200"), (TOKEN_HEADER, "secret")];
+        assert_eq!(
+            token().check_request(&request("GET", "/", &headers), true),
+            Ok(Access::Granted)
+        );
+        assert_eq!(
+            token()
+                .check_request(&request("POST", "/update", &headers), true)
+                .map_err(|(status, _)| status),
+            Err(StatusCode::FORBIDDEN)
+        );
+    }
+}
@@ -0,0 +1,343 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::ReportConfig;
+use crate::report::render_pair_diff;
+use axum::body::Bytes;
+use kompari::{DirDiffConfig, ImageDifference, PairResult, SizeOptimizationLevel};
+use maud::{Markup, PreEscaped, html};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Modification time and size of a file, `None` if the file does not exist.
+type FileStamp = Option<(SystemTime, u64)>;
+
+fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Image of a case served by the review server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ImageKind {
+    Left,
+    Right,
+    /// Difference image of the named method.
+    Diff(String),
+}
+
+impl ImageKind {
+    /// Kind and case name of an image URL, e.g. `diff` and `name.png/method`.
+    pub(crate) fn parse<'a>(kind: &str, path: &'a str) -> Option<(Self, &'a str)> {
+        match kind {
+            "left" => Some((Self::Left, path)),
+            "right" => Some((Self::Right, path)),
+            "diff" => {
+                let (name, method) = path.rsplit_once('/')?;
+                Some((Self::Diff(method.to_string()), name))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Encoded image of a case.
+pub(crate) struct CaseImage {
+    pub(crate) data: Bytes,
+    /// `false` if the file changed since the case was compared, so the image
+    /// does not belong to the version of the case.
+    pub(crate) is_current: bool,
+}
+
+/// Compared pair with its encoded difference images.
+pub(crate) struct CaseImages {
+    /// Unique for each computed result, so it identifies versions of images of the case.
+    id: usize,
+    stamps: (FileStamp, FileStamp),
+    result: Arc<PairResult>,
+    /// Encoded difference images by method.
+    diff_pngs: Mutex<HashMap<String, Bytes>>,
+}
+
+impl CaseImages {
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+
+    pub(crate) fn result(&self) -> &Arc<PairResult> {
+        &self.result
+    }
+
+    /// Left or right image read from its file, or difference image encoded once and cached.
+    pub(crate) fn image(
+        &self,
+        kind: &ImageKind,
+        size_optimization: SizeOptimizationLevel,
+    ) -> kompari::Result<CaseImage> {
+        let (path, stamp) = match kind {
+            ImageKind::Left => (&self.result.left, &self.stamps.0),
+            ImageKind::Right => (&self.result.right, &self.stamps.1),
+            ImageKind::Diff(method) => {
+                return Ok(CaseImage {
+                    data: self.diff_png(method, size_optimization)?,
+                    is_current: true,
+                });
+            }
+        };
+        let data = kompari::read_png_resolve_lfs(path)?;
+        // The file may have been changed after the comparison, before the next refresh
+        Ok(CaseImage {
+            data: Bytes::from(data),
+            is_current: file_stamp(path) == *stamp,
+        })
+    }
+
+    fn diff_png(
+        &self,
+        method: &str,
+        size_optimization: SizeOptimizationLevel,
+    ) -> kompari::Result<Bytes> {
+        // The lock is held while encoding, so each image is encoded only once
+        let mut diff_pngs = self.diff_pngs.lock().unwrap();
+        if let Some(data) = diff_pngs.get(method) {
+            return Ok(data.clone());
+        }
+        let diff_image = match &self.result.image_diff {
+            Ok(ImageDifference::Content { diff_images, .. }) => diff_images
+                .iter()
+                .find(|diff_image| diff_image.method.to_string() == method),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            kompari::Error::GenericError(format!("No difference image '{method}'").into())
+        })?;
+        let data = Bytes::from(kompari::image_to_png(&diff_image.image, size_optimization));
+        diff_pngs.insert(method.to_string(), data.clone());
+        Ok(data)
+    }
+}
+
+struct CacheEntry {
+    stamps: (FileStamp, FileStamp),
+    case: Option<Arc<CaseImages>>,
+    /// Rendered case, `None` if the pair is not reported (e.g. images match).
+    html: Option<String>,
+    is_match: bool,
+}
+
+/// Change of a case sent to open review pages.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct CaseUpdate {
+    name: String,
+    /// New content of the case, `None` if the case was removed.
+    html: Option<String>,
+}
+
+/// Rendered cases of the review, recomputed only for pairs whose files changed.
+#[derive(Default)]
+pub(crate) struct DiffCache {
+    entries: BTreeMap<String, CacheEntry>,
+    next_id: usize,
+}
+
+impl DiffCache {
+    /// Compare pairs whose files changed since the last refresh and return changed cases.
+    ///
+    /// All pairs are listed again on every call; files are compared by size and
+    /// modification time, so a change that keeps both is not detected.
+    pub(crate) fn refresh(
+        &mut self,
+        diff_config: &DirDiffConfig,
+        report_config: &ReportConfig,
+    ) -> kompari::Result<Vec<CaseUpdate>> {
+        let pairs = diff_config.pairs()?;
+        let names: BTreeSet<String> = pairs.iter().map(|pair| pair.title.clone()).collect();
+        let mut changed = Vec::new();
+        for pair in pairs {
+            let stamps = (file_stamp(&pair.left), file_stamp(&pair.right));
+            if self
+                .entries
+                .get(&pair.title)
+                .is_none_or(|entry| entry.stamps != stamps)
+            {
+                changed.push((self.next_id, stamps, pair));
+                self.next_id += 1;
+            }
+        }
+        let rendered = changed
+            .into_par_iter()
+            .map(|(id, stamps, pair)| {
+                let name = pair.title.clone();
+                let result = diff_config.compare_pair(pair);
+                let is_match = result
+                    .as_ref()
+                    .is_some_and(|r| matches!(r.image_diff, Ok(ImageDifference::None)));
+                let html = result
+                    .as_ref()
+                    .map(|result| {
+                        render_pair_diff(report_config, id, result).map(|markup| {
+                            html! {
+                                div class="case" data-case=(result.title) { (markup) }
+                            }
+                            .into_string()
+                        })
+                    })
+                    .transpose()?;
+                let case = result.map(|result| {
+                    Arc::new(CaseImages {
+                        id,
+                        stamps,
+                        result: Arc::new(result),
+                        diff_pngs: Mutex::default(),
+                    })
+                });
+                Ok((
+                    name,
+                    CacheEntry {
+                        stamps,
+                        case,
+                        html,
+                        is_match,
+                    },
+                ))
+            })
+            .collect::<kompari::Result<Vec<_>>>()?;
+
+        let mut updates = Vec::new();
+        for (name, entry) in rendered {
+            let was_shown = self
+                .entries
+                .get(&name)
+                .is_some_and(|old| old.html.is_some());
+            if was_shown || entry.html.is_some() {
+                updates.push(CaseUpdate {
+                    name: name.clone(),
+                    html: entry.html.clone(),
+                });
+            }
+            self.entries.insert(name, entry);
+        }
+        let removed: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|name| !names.contains(*name))
+            .cloned()
+            .collect();
+        for name in removed {
+            if self.entries.remove(&name).is_some_and(|e| e.html.is_some()) {
+                updates.push(CaseUpdate { name, html: None });
+            }
+        }
+        Ok(updates)
+    }
+
+    /// Reported result of a case with its images.
+    pub(crate) fn get(&self, name: &str) -> Option<Arc<CaseImages>> {
+        self.entries.get(name)?.case.clone()
+    }
+
+    /// Names of reported cases that are not matches.
+    pub(crate) fn case_names(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.html.is_some() && !entry.is_match)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Number of reported cases that are not matches.
+    pub(crate) fn n_cases(&self) -> usize {
+        self.case_names().count()
+    }
+
+    /// All rendered cases.
+    pub(crate) fn render(&self) -> Markup {
+        html! {
+            @for html in self.entries.values().filter_map(|entry| entry.html.as_ref()) {
+                (PreEscaped(html))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn copy_asset(asset: &str, target: &Path) {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+        std::fs::copy(assets.join("tests").join(asset), target).unwrap();
+    }
+
+    /// Names of updated cases and whether they are shown.
+    fn refresh(cache: &mut DiffCache, config: &DirDiffConfig) -> Vec<(String, bool)> {
+        cache
+            .refresh(config, &ReportConfig::default())
+            .unwrap()
+            .into_iter()
+            .map(|update| (update.name, update.html.is_some()))
+            .collect()
+    }
+
+    #[test]
+    fn test_refresh() {
+        let workdir = TempDir::new().unwrap();
+        let left = workdir.path().join("left");
+        let right = workdir.path().join("right");
+        std::fs::create_dir(&left).unwrap();
+        std::fs::create_dir(&right).unwrap();
+        let config = DirDiffConfig::new(left.clone(), right.clone());
+        let path = |dir: &PathBuf, name: &str| dir.join(name);
+        copy_asset("left/bright.png", &path(&left, "a.png"));
+        copy_asset("right/bright.png", &path(&right, "a.png"));
+
+        let mut cache = DiffCache::default();
+        assert_eq!(refresh(&mut cache, &config), [("a.png".to_string(), true)]);
+        assert!(refresh(&mut cache, &config).is_empty());
+        let id = cache.get("a.png").unwrap().id();
+
+        // Added pair
+        copy_asset("left/shift.png", &path(&left, "b.png"));
+        copy_asset("right/shift.png", &path(&right, "b.png"));
+        assert_eq!(refresh(&mut cache, &config), [("b.png".to_string(), true)]);
+        assert_eq!(cache.case_names().collect::<Vec<_>>(), ["a.png", "b.png"]);
+
+        // Modified image
+        copy_asset("right/changetext.png", &path(&right, "a.png"));
+        // Until the next refresh, the new image does not belong to the compared version
+        let case = cache.get("a.png").unwrap();
+        let optimization = SizeOptimizationLevel::None;
+        assert!(
+            case.image(&ImageKind::Left, optimization)
+                .unwrap()
+                .is_current
+        );
+        assert!(
+            !case
+                .image(&ImageKind::Right, optimization)
+                .unwrap()
+                .is_current
+        );
+        assert_eq!(refresh(&mut cache, &config), [("a.png".to_string(), true)]);
+        assert_ne!(cache.get("a.png").unwrap().id(), id);
+
+        // The pair becomes a match and is not reported anymore
+        copy_asset("left/shift.png", &path(&right, "b.png"));
+        assert_eq!(refresh(&mut cache, &config), [("b.png".to_string(), false)]);
+        assert!(cache.get("b.png").is_none());
+        assert_eq!(cache.n_cases(), 1);
+
+        // Removed pair
+        std::fs::remove_file(path(&left, "a.png")).unwrap();
+        std::fs::remove_file(path(&right, "a.png")).unwrap();
+        assert_eq!(refresh(&mut cache, &config), [("a.png".to_string(), false)]);
+        assert!(cache.get("a.png").is_none());
+        assert_eq!(cache.n_cases(), 0);
+        assert!(refresh(&mut cache, &config).is_empty());
+    }
+}
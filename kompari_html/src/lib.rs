@@ -12,9 +12,15 @@
 // END LINEBENDER LINT SET
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod access;
+mod diffcache;
+mod markdown;
+mod mergereport;
+mod multireport;
 mod pageconsts;
 mod report;
 mod review;
+mod reviewstate;
 
 #[derive(Debug, Clone)]
 pub struct ReportConfig {
@@ -22,6 +28,10 @@ pub struct ReportConfig {
     right_title: String,
     embed_images: bool,
     is_review: bool,
+    /// Images are linked to the review server instead of files or embedded data.
+    serve_images: bool,
+    /// Token sent by the page with requests to the review server.
+    session_token: Option<String>,
     size_optimization: SizeOptimizationLevel,
 }
 
@@ -32,6 +42,8 @@ impl Default for ReportConfig {
             right_title: "Right image".to_string(),
             embed_images: false,
             is_review: false,
+            serve_images: false,
+            session_token: None,
             size_optimization: SizeOptimizationLevel::None,
         }
     }
@@ -56,5 +68,9 @@ impl ReportConfig {
 }
 
 use kompari::SizeOptimizationLevel;
+pub use markdown::render_markdown_report;
+pub use mergereport::render_three_way_html_report;
+pub use multireport::render_multi_html_report;
 pub use report::render_html_report;
-pub use review::start_review_server;
+pub use review::{ReviewConfig, ReviewServer, start_review_server};
+pub use reviewstate::{CaseReview, ReviewDecision, ReviewState, ReviewSummary};
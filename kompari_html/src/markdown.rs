@@ -0,0 +1,135 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::ReportConfig;
+use kompari::{ImageDifference, PairResult, image_to_png};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn format_size(size: (u32, u32)) -> String {
+    format!("{}x{}", size.0, size.1)
+}
+
+/// Returns `(status, different pixels, size)` columns of a pair.
+fn pair_columns(config: &ReportConfig, result: &PairResult) -> (String, String, String) {
+    match &result.image_diff {
+        Ok(ImageDifference::None) => ("Match".into(), String::new(), String::new()),
+        Ok(ImageDifference::SizeMismatch {
+            left_size,
+            right_size,
+        }) => (
+            "Size mismatch".into(),
+            String::new(),
+            format!("{} → {}", format_size(*left_size), format_size(*right_size)),
+        ),
+        Ok(ImageDifference::Content {
+            n_pixels,
+            n_different_pixels,
+            diff_images,
+            ..
+        }) => {
+            let pct = *n_different_pixels as f32 / *n_pixels as f32 * 100.0;
+            let size = diff_images
+                .first()
+                .map(|d| format_size((d.image.width, d.image.height)))
+                .unwrap_or_default();
+            (
+                "Different".into(),
+                format!("{n_different_pixels} ({pct:.1}%)"),
+                size,
+            )
+        }
+        Err(e) if e.is_left_missing() && e.is_right_missing() => {
+            ("Missing both".into(), String::new(), String::new())
+        }
+        Err(e) if e.is_left_missing() => (
+            format!("Missing: {}", config.left_title),
+            String::new(),
+            String::new(),
+        ),
+        Err(e) if e.is_right_missing() => (
+            format!("Missing: {}", config.right_title),
+            String::new(),
+            String::new(),
+        ),
+        Err(_) => ("Loading error".into(), String::new(), String::new()),
+    }
+}
+
+/// Render a compact Markdown summary of pairs, e.g. for a pull request comment
+/// or a CI step summary.
+///
+/// If `output_path` is given, diff images are written into the directory
+/// `<output name>-images` next to it and linked relatively from the table.
+pub fn render_markdown_report(
+    config: &ReportConfig,
+    results: &[PairResult],
+    output_path: Option<&Path>,
+) -> kompari::Result<String> {
+    let image_dir = output_path.map(|path| {
+        let mut name = path.file_stem().unwrap_or_default().to_os_string();
+        name.push("-images");
+        (path.with_file_name(&name), PathBuf::from(name))
+    });
+    let mut report = String::new();
+    // Matches are listed only in HTML reports
+    let different: Vec<&PairResult> = results
+        .iter()
+        .filter(|r| !matches!(r.image_diff, Ok(ImageDifference::None)))
+        .collect();
+    let n_different = different.len();
+    if n_different == 0 {
+        report.push_str("**Kompari:** all images match\n");
+        return Ok(report);
+    }
+    writeln!(report, "**Kompari:** {n_different} image(s) differ\n").unwrap();
+    report.push_str("| Image | Status | Different pixels | Size |");
+    if image_dir.is_some() {
+        report.push_str(" Diff |");
+    }
+    report.push_str("\n|---|---|---|---|");
+    if image_dir.is_some() {
+        report.push_str("---|");
+    }
+    report.push('\n');
+    for (idx, result) in different.into_iter().enumerate() {
+        let (status, pixels, size) = pair_columns(config, result);
+        write!(
+            report,
+            "| {} | {} | {} | {} |",
+            escape_cell(&result.title),
+            escape_cell(&status),
+            pixels,
+            size
+        )
+        .unwrap();
+        if let Some((dir, link_dir)) = &image_dir {
+            let diff_image = match &result.image_diff {
+                Ok(ImageDifference::Content { diff_images, .. }) => diff_images.first(),
+                _ => None,
+            };
+            if let Some(diff_image) = diff_image {
+                std::fs::create_dir_all(dir)?;
+                let name = format!("{idx:04}.png");
+                std::fs::write(
+                    dir.join(&name),
+                    image_to_png(&diff_image.image, config.size_optimization),
+                )?;
+                let link = link_dir.join(&name);
+                let link = link
+                    .to_string_lossy()
+                    .replace('\\', "/")
+                    .replace(' ', "%20");
+                write!(report, " [![diff]({link})]({link}) |").unwrap();
+            } else {
+                report.push_str("  |");
+            }
+        }
+        report.push('\n');
+    }
+    Ok(report)
+}
@@ -0,0 +1,105 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::ReportConfig;
+use crate::report::{render_difference_image, render_image, render_page, render_stat_item};
+use kompari::{MergeStatus, ThreeWayDiff, ThreeWayPairResult};
+use maud::{Markup, html};
+use rayon::iter::IndexedParallelIterator;
+use rayon::iter::IntoParallelRefIterator;
+use rayon::iter::ParallelIterator;
+
+fn render_merge_status(status: MergeStatus) -> Markup {
+    let (value_type, label) = match status {
+        MergeStatus::Unchanged => ("ok", "Unchanged"),
+        MergeStatus::ChangedInOurs => ("ok", "Changed in ours"),
+        MergeStatus::ChangedInTheirs => ("ok", "Changed in theirs"),
+        MergeStatus::BothChangedIdentical => ("ok", "Both changed (identical)"),
+        MergeStatus::Conflict => ("error", "Conflict"),
+    };
+    render_stat_item("Status", value_type, label)
+}
+
+fn render_three_way_diff(
+    config: &ReportConfig,
+    id: usize,
+    pair: &ThreeWayPairResult,
+) -> kompari::Result<Markup> {
+    let (ours_error, theirs_error) = match &pair.result {
+        Ok(_) => (None, None),
+        Err(e) => (e.left(), e.right()),
+    };
+    // The base is optional, so it is shown as missing whenever it was not used
+    let base_missing = kompari::Error::FileNotFound(pair.base.clone());
+    let base_error = match &pair.result {
+        Ok(result) if result.base_ours.is_some() => None,
+        _ => Some(&base_missing),
+    };
+    let result = pair.result.as_ref().ok();
+    Ok(html! {
+        div class="diff-entry" {
+            h2 { (pair.title) }
+            div class="comparison-container" {
+                div class="image-container" {
+                    div class="stats-container" {
+                        @match &pair.result {
+                            Ok(result) => (render_merge_status(result.status)),
+                            Err(e) if e.is_missing_file_error() => (render_stat_item("Status", "error", "Missing file")),
+                            Err(_) => (render_stat_item("Status", "error", "Loading error")),
+                        }
+                    }
+                    div class="image-box" {
+                        h3 { "Base" }
+                        (render_image(config, &pair.base, base_error, None)?)
+                    }
+                    div class="image-box" {
+                        h3 { "Ours" }
+                        (render_image(config, &pair.ours, ours_error, None)?)
+                    }
+                    div class="image-box" {
+                        h3 { "Theirs" }
+                        (render_image(config, &pair.theirs, theirs_error, None)?)
+                    }
+                }
+            }
+            div class="comparison-container" {
+                div class="image-container three-way-diffs" {
+                    div class="image-box" {
+                        h3 { "Ours vs. base" }
+                        (render_difference_image(config, id * 3, result.and_then(|r| r.base_ours.as_ref()), None))
+                    }
+                    div class="image-box" {
+                        h3 { "Theirs vs. base" }
+                        (render_difference_image(config, id * 3 + 1, result.and_then(|r| r.base_theirs.as_ref()), None))
+                    }
+                    div class="image-box" {
+                        h3 { "Ours vs. theirs" }
+                        (render_difference_image(config, id * 3 + 2, result.map(|r| &r.ours_theirs), None))
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Render a report of a three-way comparison (base, ours, theirs).
+///
+/// Each image shows its merge status, the three versions, and the differences of both sides
+/// against the base and against each other.
+pub fn render_three_way_html_report(
+    config: &ReportConfig,
+    diff: &ThreeWayDiff,
+) -> kompari::Result<String> {
+    let rendered_diffs: Vec<Markup> = diff
+        .results()
+        .par_iter()
+        .enumerate()
+        .map(|(id, pair)| render_three_way_diff(config, id, pair))
+        .collect::<kompari::Result<Vec<_>>>()?;
+    Ok(render_page(
+        config,
+        diff.results().len(),
+        rendered_diffs,
+        false,
+    ))
+}
@@ -0,0 +1,77 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::ReportConfig;
+use crate::report::{render_difference_image, render_difference_info, render_image, render_page};
+use kompari::{MultiDiff, MultiPairResult};
+use maud::{Markup, html};
+use rayon::iter::IndexedParallelIterator;
+use rayon::iter::IntoParallelRefIterator;
+use rayon::iter::ParallelIterator;
+
+fn render_multi_pair_diff(
+    config: &ReportConfig,
+    candidate_configs: &[ReportConfig],
+    id: usize,
+    multi_diff: &MultiPairResult,
+) -> kompari::Result<Markup> {
+    let reference_error = multi_diff
+        .candidates
+        .first()
+        .and_then(|result| result.image_diff.as_ref().err())
+        .and_then(|e| e.left());
+    let n_candidates = candidate_configs.len();
+    Ok(html! {
+        div class="diff-entry" {
+            h2 { (multi_diff.title) }
+            div class="image-container" {
+                div class="image-box" {
+                    h3 { (config.left_title) }
+                    (render_image(config, &multi_diff.reference, reference_error, None)?)
+                }
+                @for (idx, (candidate_config, result)) in candidate_configs.iter().zip(&multi_diff.candidates).enumerate() {
+                    div class="image-box" {
+                        h3 { (candidate_config.right_title) }
+                        (render_image(config, &result.right, if let Err(e) = &result.image_diff { e.right() } else { None }, None)?)
+                        div class="stats-container candidate-stats" {
+                            (render_difference_info(candidate_config, &result.image_diff))
+                        }
+                        (render_difference_image(config, id * n_candidates + idx, result.image_diff.as_ref().ok(), None))
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Render a report of an N-way comparison.
+///
+/// Each image gets one row with the reference image followed by a column for each candidate
+/// with the candidate image, statistics and the difference against the reference.
+pub fn render_multi_html_report(
+    config: &ReportConfig,
+    diff: &MultiDiff,
+) -> kompari::Result<String> {
+    // Candidate names are used in place of the right title
+    let candidate_configs: Vec<_> = diff
+        .candidate_names()
+        .iter()
+        .map(|name| {
+            let mut candidate_config = config.clone();
+            candidate_config.set_right_title(name);
+            candidate_config
+        })
+        .collect();
+    let rendered_diffs: Vec<Markup> = diff
+        .results()
+        .par_iter()
+        .enumerate()
+        .map(|(id, multi_diff)| render_multi_pair_diff(config, &candidate_configs, id, multi_diff))
+        .collect::<kompari::Result<Vec<_>>>()?;
+    Ok(render_page(
+        config,
+        diff.results().len(),
+        rendered_diffs,
+        false,
+    ))
+}
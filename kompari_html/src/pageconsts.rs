@@ -37,6 +37,26 @@ body {
     box-shadow: 0 2px 4px rgba(0,0,0,0.1);
 }
 
+.toolbar {
+    display: flex;
+    flex-wrap: wrap;
+    align-items: center;
+    gap: 12px;
+    margin-bottom: 20px;
+    padding: 15px;
+    background: #fff;
+    border-radius: 8px;
+    box-shadow: 0 2px 4px rgba(0,0,0,0.1);
+}
+
+.toolbar input[type=search] {
+    flex: 1;
+    min-width: 200px;
+    padding: 6px 10px;
+    border: 1px solid #e2e8f0;
+    border-radius: 4px;
+}
+
 .diff-entry {
     background: #fff;
     margin-bottom: 30px;
@@ -52,6 +72,28 @@ body {
     padding-bottom: 10px;
 }
 
+.match-entry summary {
+    cursor: pointer;
+    list-style: none;
+}
+
+.match-entry summary h2 {
+    margin-bottom: 0;
+    border-bottom: none;
+    padding-bottom: 0;
+}
+
+.match-entry[open] summary h2 {
+    border-bottom: 2px solid #edf2f7;
+    padding-bottom: 10px;
+}
+
+.match-label {
+    margin-left: 10px;
+    font-size: 0.6em;
+    color: #38a169;
+}
+
 .comparison-container {
     display: flex;
     gap: 20px;
@@ -96,6 +138,15 @@ body {
     margin-bottom: 15px;
 }
 
+.three-way-diffs {
+    margin-left: 252px;
+}
+
+.candidate-stats {
+    width: auto;
+    margin: 10px 0;
+}
+
 .stat-label {
     font-size: 0.875rem;
     color: #64748b;
@@ -125,6 +176,10 @@ body {
         flex-direction: column-reverse;
     }
 
+    .three-way-diffs {
+        margin-left: 0;
+    }
+
     .stats-container {
         width: auto;
         display: flex;
@@ -169,45 +224,30 @@ dialog {
     image-rendering: pixelated;
 }
 
-.toggle-switch {
-  position: relative;
-  display: inline-block;
-  width: 60px;
-  height: 30px;
-  margin-right: 1em;
-}
-.toggle-switch input {
-  opacity: 0;
-  width: 0;
-  height: 0;
+.review-controls {
+  display: flex;
+  flex-wrap: wrap;
+  align-items: center;
+  gap: 12px;
 }
-.slider {
-  position: absolute;
+.decision {
   cursor: pointer;
-  top: 0;
-  left: 0;
-  right: 0;
-  bottom: 0;
-  background-color: #ccc;
-  transition: .1s;
-  border-radius: 34px;
-}
-.slider:before {
-  position: absolute;
-  content: \"\";
-  height: 22px;
-  width: 22px;
-  left: 4px;
-  bottom: 4px;
-  background-color: white;
-  transition: .1s;
-  border-radius: 50%;
-}
-input:checked + .slider {
-  background-color: #3c3;
-}
-input:checked + .slider:before {
-  transform: translateX(30px);
+}
+.review-comment {
+  flex: 1;
+  min-width: 200px;
+  padding: 4px 8px;
+  border: 1px solid #e2e8f0;
+  border-radius: 4px;
+}
+.diff-entry[data-decision=accepted] h2 {
+  color: #3a3;
+}
+.diff-entry[data-decision=rejected] h2 {
+  color: #dc2626;
+}
+.diff-entry[data-decision=follow-up] h2 {
+  color: #d97706;
 }
 
 .accept-button {
@@ -239,6 +279,23 @@ input:checked + .slider:before {
   cursor: not-allowed;
   transform: none;
 }
+.undo-button {
+  padding: 8px 16px;
+  margin-bottom: 1em;
+  font-size: 14px;
+  color: #2d3748;
+  background-color: #edf2f7;
+  border: none;
+  border-radius: 6px;
+  cursor: pointer;
+}
+.undo-button:hover {
+  background-color: #e2e8f0;
+}
+.undo-button:disabled {
+  color: #a0aec0;
+  cursor: not-allowed;
+}
 #errorMsg {
     background-color: #fef2f2;
     border: 1px solid #f87171;
@@ -315,26 +372,188 @@ document.getElementById('imageDialog').addEventListener('click', function(event)
 });
 
 var selected = new Set();
-function toggle(event) {
-    let node = event.target.parentNode.parentNode;
-    let name = node.childNodes[2].textContent;
-    if (event.target.checked) {
-        selected.add(name);
-        node.style.color = \"#3a3\";
+function applyDecision(node, decision) {
+    node.dataset.decision = decision;
+    if (decision === \"accepted\") {
+        selected.add(node.dataset.name);
     } else {
-        selected.delete(name);
-        node.style.color = \"#333\";
+        selected.delete(node.dataset.name);
+    }
+    updateAcceptButton();
+}
+
+function showError(message) {
+    let error = document.getElementById('errorMsg');
+    error.textContent = message;
+    error.style.display = \"flex\";
+}
+
+// Error of a failed request; rejected case names are listed if the server sent them
+async function responseError(response, message) {
+    if (response.status === 400) {
+        try {
+            const body = await response.json();
+            return new Error(`${message}: ${body.error}: ${body.rejected.join(\", \")}`);
+        } catch (e) {}
+    }
+    return new Error(`${message}, response status: ${response.status}`);
+}
+
+async function reviewChanged(event) {
+    let node = event.target.closest('.diff-entry');
+    let decision = node.querySelector('.review-controls input[type=radio]:checked').value;
+    let comment = node.querySelector('.review-comment').value;
+    applyDecision(node, decision);
+    applyToolbar();
+    try {
+        const response = await fetch('/review', {
+            method: 'POST',
+            headers: {
+               \"Content-Type\": \"application/json\",
+               \"X-Kompari-Token\": sessionToken,
+            },
+            body: JSON.stringify({ name: node.dataset.name, decision: decision, comment: comment })
+        });
+        if (!response.ok) {
+            throw await responseError(response, \"Saving review failed\");
+        }
+    } catch (e) {
+        showError(e.message);
+    }
+}
+
+async function loadReview(root = document) {
+    try {
+        const response = await fetch('/review');
+        if (!response.ok) {
+            throw new Error(`Loading review failed, response status: ${response.status}`);
+        }
+        const cases = await response.json();
+        root.querySelectorAll('.diff-entry[data-name]').forEach((node) => {
+            let review = cases[node.dataset.name];
+            if (review) {
+                node.querySelector(`input[value=\"${review.decision}\"]`).checked = true;
+                node.querySelector('.review-comment').value = review.comment || \"\";
+                applyDecision(node, review.decision);
+            }
+        });
+        applyToolbar();
+    } catch (e) {
+        showError(e.message);
     }
-    updateAcceptButton()
 }
 
+var events = null;
+
+// Scripts inserted through innerHTML are not executed, so they are replaced by new ones
+function runScripts(node) {
+    node.querySelectorAll('script').forEach((old) => {
+        let script = document.createElement('script');
+        script.textContent = old.textContent;
+        old.replaceWith(script);
+    });
+}
+
+function applyCaseUpdates(updates) {
+    let container = document.getElementById('cases');
+    for (const update of updates) {
+        let old = container.querySelector(`:scope > .case[data-case=\"${CSS.escape(update.name)}\"]`);
+        if (old) {
+            old.remove();
+            selected.delete(update.name);
+        }
+        if (update.html !== null) {
+            let template = document.createElement('template');
+            template.innerHTML = update.html;
+            let node = template.content.firstElementChild;
+            let next = Array.from(container.children).find((c) => c.dataset.case > update.name);
+            container.insertBefore(node, next || null);
+            runScripts(node);
+            if (document.getElementById('acceptButton')) {
+                loadReview(node);
+            }
+        }
+    }
+    if (document.getElementById('acceptButton')) {
+        nTests = container.querySelectorAll('.review-controls').length;
+        updateAcceptButton();
+    }
+    applyToolbar();
+}
+
+window.addEventListener('DOMContentLoaded', () => {
+    if (document.getElementById('acceptButton')) {
+        loadReview();
+    }
+    if (document.getElementById('cases').dataset.live !== undefined) {
+        events = new EventSource('/events');
+        events.addEventListener('cases', (event) => applyCaseUpdates(JSON.parse(event.data)));
+        events.addEventListener('reload', () => location.reload());
+    }
+});
+
 function updateAcceptButton() {
     let text = document.getElementById('acceptText');
-    text.textContent = \"Accept selected cases (\" + selected.size + \" / \" + nTests + \")\";
+    text.textContent = \"Update accepted cases (\" + selected.size + \" / \" + nTests + \")\";
     let button = document.getElementById('acceptButton');
     button.disabled = (selected.size === 0);
 }
 
+// Entry with data attributes of a case; in the review the entry is wrapped in a `.case` element
+function caseEntry(item) {
+    return item.classList.contains('diff-entry') ? item : item.querySelector('.diff-entry');
+}
+
+function caseMetric(item, name) {
+    let value = caseEntry(item).dataset[name];
+    // Cases without metrics (size mismatches, missing files, errors) go first
+    return value === undefined ? Infinity : Number(value);
+}
+
+function compareTitles(a, b) {
+    let ta = caseEntry(a).dataset.title;
+    let tb = caseEntry(b).dataset.title;
+    return ta < tb ? -1 : (ta > tb ? 1 : 0);
+}
+
+function applyToolbar() {
+    let container = document.getElementById('cases');
+    if (!document.getElementById('toolbar')) {
+        return;
+    }
+    let search = document.getElementById('searchInput').value.toLowerCase();
+    let status = document.getElementById('statusFilter').value;
+    let sort = document.getElementById('sortOrder').value;
+    let onlySelected = document.getElementById('onlySelected');
+    let items = Array.from(container.children);
+    let nVisible = 0;
+    for (const item of items) {
+        let entry = caseEntry(item);
+        let visible = entry.dataset.title.toLowerCase().includes(search)
+            && (status === \"all\" || entry.dataset.status === status)
+            && (!onlySelected || !onlySelected.checked || selected.has(entry.dataset.title));
+        item.style.display = visible ? \"\" : \"none\";
+        if (visible) {
+            nVisible++;
+        }
+    }
+    if (sort === \"title\") {
+        items.sort(compareTitles);
+    } else {
+        items.sort((a, b) => {
+            let va = caseMetric(a, sort);
+            let vb = caseMetric(b, sort);
+            // Values are compared explicitly as `Infinity - Infinity` is NaN;
+            // cases with equal values are sorted by name
+            return vb > va ? 1 : (vb < va ? -1 : compareTitles(a, b));
+        });
+    }
+    items.forEach((item) => container.appendChild(item));
+    document.getElementById('visibleCount').textContent = `Showing ${nVisible} of ${items.length}`;
+}
+
+window.addEventListener('DOMContentLoaded', applyToolbar);
+
 function switchDiffTab(id, selected, n) {
     for (let idx = 0; idx < n; idx++) {
         document.getElementById(`tab-diff-${id}-${idx}`).classList.remove('active');
@@ -356,23 +575,70 @@ async function acceptTests() {
             method: 'POST',
             headers: {
                \"Content-Type\": \"application/json\",
+               \"X-Kompari-Token\": sessionToken,
             },
             body: JSON.stringify({ accepted_names: Array.from(selected) })
         });
         if (!response.ok) {
-          throw new Error(`Response status: ${response.status}`);
+          throw await responseError(response, \"Update failed\");
         } else {
-          for (i = 0; i < nTests; i++) {
-                document.getElementById(\"t\" + i).checked = false;
-          }
           location.reload();
         }
     } catch (e) {
-        let error = document.getElementById('errorMsg');
-        error.textContent = e.message;
-        error.style.display = \"flex\";
+        showError(e.message);
         text.textContent = \"Try update again\";
         button.disabled = false;
     }
 }
+
+async function undoAccept() {
+    let button = document.getElementById('undoButton');
+    button.disabled = true;
+    try {
+        const response = await fetch('/undo', {
+            method: 'POST',
+            headers: { \"X-Kompari-Token\": sessionToken },
+        });
+        if (response.status === 409) {
+            throw new Error(\"Nothing to undo\");
+        } else if (!response.ok) {
+            throw new Error(`Response status: ${response.status}`);
+        }
+        location.reload();
+    } catch (e) {
+        showError(e.message);
+        button.disabled = false;
+    }
+}
+
+async function finishReview() {
+    let button = document.getElementById('finishButton');
+    button.disabled = true;
+    try {
+        const response = await fetch('/finish', {
+            method: 'POST',
+            headers: { \"X-Kompari-Token\": sessionToken },
+        });
+        if (!response.ok) {
+            throw new Error(`Response status: ${response.status}`);
+        }
+        const summary = await response.json();
+        if (events) {
+            events.close();
+        }
+        let cases = document.getElementById('cases');
+        cases.replaceChildren();
+        let message = document.createElement('div');
+        message.className = \"summary\";
+        message.textContent = `Review finished: ${summary.accepted} accepted, ${summary.rejected} rejected, `
+            + `${summary.follow_up} needing follow-up, ${summary.pending} pending. The server was stopped.`;
+        cases.appendChild(message);
+        document.querySelectorAll('#toolbar, .accept-button, .undo-button, .hint').forEach((node) => {
+            node.style.display = \"none\";
+        });
+    } catch (e) {
+        showError(e.message);
+        button.disabled = false;
+    }
+}
 ";
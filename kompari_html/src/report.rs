@@ -23,26 +23,68 @@ fn embed_png_url(data: &[u8]) -> String {
     url
 }
 
-fn render_image(
+/// Percent-encode a case name for use in an URL path; slashes are kept.
+fn encode_url_path(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            result.push(byte as char);
+        } else {
+            result.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    result
+}
+
+/// URL of an image served by the review server, `kind` is `left`, `right` or `diff`.
+pub(crate) fn image_url(kind: &str, name: &str) -> String {
+    format!("/image/{kind}/{}", encode_url_path(name))
+}
+
+/// Image served from `url` instead of the file, loaded when it is scrolled into view.
+fn render_served_image(url: &str, path: &Path) -> kompari::Result<Markup> {
+    let size = match imagesize::size(path) {
+        Ok(size) => size,
+        Err(_) => imagesize::blob_size(&kompari::read_png_resolve_lfs(path)?)
+            .map_err(|e| kompari::Error::GenericError(Box::new(e)))?,
+    };
+    let (w, h) = html_size(size.width, size.height, IMAGE_SIZE_LIMIT);
+    Ok(html! {
+        img class="zoom" src=(url) loading="lazy"
+            width=[w] height=[h]
+            style=(format!("aspect-ratio: {} / {}", size.width, size.height))
+            onclick=(open_image_dialog(size.width, size.height));
+    })
+}
+
+pub(crate) fn render_image(
     config: &ReportConfig,
     path: &Path,
     error: Option<&kompari::Error>,
+    url: Option<&str>,
 ) -> kompari::Result<Markup> {
     Ok(match error {
         None => {
-            let (path, size) = if config.embed_images {
-                let image_data =
-                    kompari::optimize_png(std::fs::read(path)?, config.size_optimization);
+            if let Some(url) = url {
+                return render_served_image(url, path);
+            }
+            // Files that are not directly readable images (e.g. resolved Git LFS pointers)
+            // are always embedded
+            let linked_size = (!config.embed_images)
+                .then(|| imagesize::size(path).ok())
+                .flatten();
+            let (path, size) = if let Some(size) = linked_size {
+                (path.display().to_string(), size)
+            } else {
+                let image_data = kompari::optimize_png(
+                    kompari::read_png_resolve_lfs(path)?,
+                    config.size_optimization,
+                );
                 (
                     embed_png_url(&image_data),
                     imagesize::blob_size(&image_data)
                         .map_err(|e| kompari::Error::GenericError(Box::new(e)))?,
                 )
-            } else {
-                (
-                    path.display().to_string(),
-                    imagesize::size(path).map_err(|e| kompari::Error::GenericError(Box::new(e)))?,
-                )
             };
             let (w, h) = html_size(size.width, size.height, IMAGE_SIZE_LIMIT);
             html! {
@@ -76,29 +118,45 @@ fn open_image_dialog(width: usize, height: usize) -> &'static str {
     }
 }
 
-fn render_difference_image(
+/// Renders tabs with difference images; images are served from `{url}/{method}` if `url` is set.
+pub(crate) fn render_difference_image(
     config: &ReportConfig,
     id: usize,
-    difference: &Result<ImageDifference, LeftRightError>,
+    difference: Option<&ImageDifference>,
+    url: Option<&str>,
 ) -> Markup {
     match difference {
-        Ok(ImageDifference::Content { diff_images, .. }) => {
+        Some(ImageDifference::Content { diff_images, .. }) => {
             html! {
                 @for (idx, di) in diff_images.iter().enumerate() {
-                    @let (w, h, data) = {
+                    @let (w, h, src) = {
                         let (w, h) = html_size(
                             di.image.width as usize,
                             di.image.height as usize,
                             IMAGE_SIZE_LIMIT,
                         );
-                        let data = kompari::image_to_png(&di.image, config.size_optimization);
-                        (w, h, data)
+                        let src = match url {
+                            Some(url) => format!("{url}/{}", di.method),
+                            None => embed_png_url(&kompari::image_to_png(&di.image, config.size_optimization)),
+                        };
+                        (w, h, src)
+                   };
+                   @let style = {
+                        // Lazily loaded images need an aspect ratio to reserve space
+                        let mut style = Vec::new();
+                        if idx > 0 {
+                            style.push("display: none".to_string());
+                        }
+                        if url.is_some() {
+                            style.push(format!("aspect-ratio: {} / {}", di.image.width, di.image.height));
+                        }
+                        (!style.is_empty()).then(|| style.join("; "))
                    };
-                   @let style = if idx == 0 { None } else { Some("display: none") };
                    img id=(format!("img-diff-{id}-{idx}"))
                        style=[style]
                        class="zoom"
-                       src=(embed_png_url(&data))
+                       src=(src)
+                       loading=[url.map(|_| "lazy")]
                        width=[w] height=[h]
                        onclick=(open_image_dialog(di.image.width as usize, di.image.height as usize));
                 }
@@ -145,7 +203,7 @@ fn render_stat_color(label: &str, value_type: &str, color: Rgba8) -> Markup {
     }
 }
 
-fn render_stat_item(label: &str, value_type: &str, value: &str) -> Markup {
+pub(crate) fn render_stat_item(label: &str, value_type: &str, value: &str) -> Markup {
     html! {
         div .stat-item {
             div .stat-label {
@@ -159,7 +217,7 @@ fn render_stat_item(label: &str, value_type: &str, value: &str) -> Markup {
     }
 }
 
-fn render_difference_info(
+pub(crate) fn render_difference_info(
     config: &ReportConfig,
     difference: &Result<ImageDifference, LeftRightError>,
 ) -> Markup {
@@ -204,54 +262,176 @@ fn render_difference_info(
     }
 }
 
-fn render_pair_diff(
+fn render_comparison(
     config: &ReportConfig,
     id: usize,
     pair_diff: &PairResult,
 ) -> kompari::Result<Markup> {
+    let url = |kind| {
+        config
+            .serve_images
+            .then(|| image_url(kind, &pair_diff.title))
+    };
     Ok(html! {
-        div class="diff-entry" {
-            h2 {
-                @if config.is_review {
-                    label class="toggle-switch" {
-                        input type="checkbox" id=(format!("t{id}"));
-                        span class="slider";
-                    }
-                    script {
-                        (format!("document.getElementById('t{id}').addEventListener('change', toggle)"))
-                    }
+        div class="comparison-container" {
+            div class="image-container" {
+                div class="stats-container" {
+                    (render_difference_info(config, &pair_diff.image_diff))
                 }
-                (pair_diff.title)};
-            div class="comparison-container" {
-                div class="image-container" {
-                    div class="stats-container" {
-                        (render_difference_info(config, &pair_diff.image_diff))
-                    }
-                    div class="image-box" {
-                        h3 { (config.left_title) }
-                        (render_image(config, &pair_diff.left, if let Err(e) = &pair_diff.image_diff { e.left() } else { None })?)
-                    }
-                    div class="image-box" {
-                        h3 { (config.right_title) }
-                        (render_image(config, &pair_diff.right, if let Err(e) = &pair_diff.image_diff { e.right() } else { None })?)
-                    }
-                    div class="image-box" {
-                        h3 { "Difference"}
-                        (render_difference_image(config, id, &pair_diff.image_diff))
-                    }
+                div class="image-box" {
+                    h3 { (config.left_title) }
+                    (render_image(config, &pair_diff.left, if let Err(e) = &pair_diff.image_diff { e.left() } else { None }, url("left").as_deref())?)
+                }
+                div class="image-box" {
+                    h3 { (config.right_title) }
+                    (render_image(config, &pair_diff.right, if let Err(e) = &pair_diff.image_diff { e.right() } else { None }, url("right").as_deref())?)
+                }
+                div class="image-box" {
+                    h3 { "Difference"}
+                    (render_difference_image(config, id, pair_diff.image_diff.as_ref().ok(), url("diff").as_deref()))
+                }
+            }
+        }
+    })
+}
+
+/// Status of a case used by the toolbar filter.
+fn case_status(image_diff: &Result<ImageDifference, LeftRightError>) -> &'static str {
+    match image_diff {
+        Ok(ImageDifference::None) => "match",
+        Ok(ImageDifference::SizeMismatch { .. }) => "size-mismatch",
+        Ok(ImageDifference::Content { .. }) => "content",
+        Err(e) if e.is_left_missing() => "missing-left",
+        Err(e) if e.is_right_missing() => "missing-right",
+        Err(_) => "error",
+    }
+}
+
+/// Percentage of different pixels and color distance used by the toolbar sorting.
+fn case_metrics(image_diff: &Result<ImageDifference, LeftRightError>) -> Option<(f32, f32)> {
+    match image_diff {
+        Ok(ImageDifference::None) => Some((0.0, 0.0)),
+        Ok(ImageDifference::Content {
+            n_pixels,
+            n_different_pixels,
+            distance_sum,
+            ..
+        }) => Some((
+            *n_different_pixels as f32 / *n_pixels as f32 * 100.0,
+            *distance_sum as f32 / 255.0,
+        )),
+        _ => None,
+    }
+}
+
+pub(crate) fn render_pair_diff(
+    config: &ReportConfig,
+    id: usize,
+    pair_diff: &PairResult,
+) -> kompari::Result<Markup> {
+    let status = case_status(&pair_diff.image_diff);
+    let metrics = case_metrics(&pair_diff.image_diff);
+    let pct = metrics.map(|(pct, _)| pct.to_string());
+    let distance = metrics.map(|(_, distance)| distance.to_string());
+    // Matching images are collapsed, as there is nothing to review
+    if matches!(pair_diff.image_diff, Ok(ImageDifference::None)) {
+        return Ok(html! {
+            details class="diff-entry match-entry"
+                data-title=(pair_diff.title) data-status=(status) data-pct=[pct] data-distance=[distance] {
+                summary {
+                    h2 { (pair_diff.title) span class="match-label" { "Match" } }
                 }
+                (render_comparison(config, id, pair_diff)?)
             }
+        });
+    }
+    Ok(html! {
+        div class="diff-entry" data-name=[config.is_review.then_some(&pair_diff.title)]
+            data-title=(pair_diff.title) data-status=(status) data-pct=[pct] data-distance=[distance] {
+            h2 { (pair_diff.title) }
+            @if config.is_review {
+                (render_review_controls(id))
+            }
+            (render_comparison(config, id, pair_diff)?)
         }
     })
 }
 
+const REVIEW_DECISIONS: [(&str, &str); 4] = [
+    ("pending", "Pending"),
+    ("accepted", "Accept"),
+    ("rejected", "Reject"),
+    ("follow-up", "Needs follow-up"),
+];
+
+fn render_review_controls(id: usize) -> Markup {
+    html! {
+        div class="review-controls" id=(format!("r{id}")) {
+            @for (value, label) in REVIEW_DECISIONS {
+                label class=(format!("decision decision-{value}")) {
+                    input type="radio" name=(format!("d{id}")) value=(value) checked[value == "pending"];
+                    (label)
+                }
+            }
+            input type="text" class="review-comment" placeholder="Comment";
+        }
+        script {
+            (format!("document.getElementById('r{id}').addEventListener('change', reviewChanged)"))
+        }
+    }
+}
+
 pub fn render_html_report(config: &ReportConfig, diffs: &[PairResult]) -> kompari::Result<String> {
-    let now = chrono::Local::now().round_subsecs(0);
     let rendered_diffs: Vec<Markup> = diffs
         .par_iter()
         .enumerate()
         .map(|(id, pair_diff)| render_pair_diff(config, id, pair_diff))
         .collect::<kompari::Result<Vec<_>>>()?;
+    let n_cases = diffs
+        .iter()
+        .filter(|pair_diff| !matches!(pair_diff.image_diff, Ok(ImageDifference::None)))
+        .count();
+    Ok(render_page(config, n_cases, rendered_diffs, true))
+}
+
+fn render_toolbar(config: &ReportConfig) -> Markup {
+    html! {
+        div class="toolbar" id="toolbar" {
+            input type="search" id="searchInput" placeholder="Search names" oninput="applyToolbar()";
+            select id="statusFilter" onchange="applyToolbar()" {
+                option value="all" { "All statuses" }
+                option value="content" { "Different content" }
+                option value="size-mismatch" { "Size mismatch" }
+                option value="missing-left" { "Missing " (config.left_title) }
+                option value="missing-right" { "Missing " (config.right_title) }
+                option value="error" { "Loading error" }
+                option value="match" { "Match" }
+            }
+            select id="sortOrder" onchange="applyToolbar()" {
+                option value="title" { "Sort by name" }
+                option value="pct" { "Sort by different pixels" }
+                option value="distance" { "Sort by color distance" }
+            }
+            @if config.is_review {
+                label {
+                    input type="checkbox" id="onlySelected" onchange="applyToolbar()";
+                    "Show only selected"
+                }
+            }
+            span class="hint" id="visibleCount" {}
+        }
+    }
+}
+
+/// Renders a page with the given cases; `toolbar` adds searching, filtering and sorting
+/// of cases rendered by [`render_pair_diff`].
+pub(crate) fn render_page(
+    config: &ReportConfig,
+    n_cases: usize,
+    rendered: Vec<Markup>,
+    toolbar: bool,
+) -> String {
+    let now = chrono::Local::now().round_subsecs(0);
     let title = PreEscaped(if config.is_review {
         "Kompari review"
     } else {
@@ -276,20 +456,31 @@ pub fn render_html_report(config: &ReportConfig, diffs: &[PairResult]) -> kompar
                 dialog id="imageDialog" {
                     img id="zoomedImage" class="zoomed-image" src="" alt="Zoomed Image";
                 }
+                @if let Some(token) = &config.session_token {
+                    script { (PreEscaped(format!("const sessionToken = \"{token}\";"))) }
+                }
                 @if config.is_review {
-                    script { (format!("const nTests = {};", diffs.len())) }
+                    script { (format!("var nTests = {};", n_cases)) }
                     button class="accept-button" id="acceptButton" disabled onClick="acceptTests()" {
-                        span class="button-text" id="acceptText" { (format!("Accept selected cases (0 / {})", diffs.len())) }
+                        span class="button-text" id="acceptText" { (format!("Update accepted cases (0 / {})", n_cases)) }
                     }
+                    button class="undo-button" id="undoButton" onClick="undoAccept()" { "Undo last accept" }
+                    button class="undo-button" id="finishButton" onClick="finishReview()" { "Finish review" }
                     span class="hint" { "Accepting a case copies '" (config.right_title) "' to '" (config.left_title) "'" }
                     span id="errorMsg" {};
                 }
                 script { (PreEscaped(JS_CODE)) }
-                @for chunk in rendered_diffs  {
-                   (chunk)
+                @if toolbar {
+                    (render_toolbar(config))
+                }
+                // Cases of pages served by the review server are updated live
+                div id="cases" data-live[config.serve_images] {
+                    @for chunk in rendered  {
+                       (chunk)
+                    }
                 }
             }
         }
     };
-    Ok(report.into_string())
+    report.into_string()
 }
@@ -1,49 +1,294 @@
 // Copyright 2025 the Kompari Authors
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use crate::{ReportConfig, render_html_report};
-use axum::extract::State;
-use axum::http::StatusCode;
-use axum::response::{Html, IntoResponse};
+use crate::access::{Access, SessionToken};
+use crate::diffcache::{CaseUpdate, DiffCache, ImageKind};
+use crate::report::render_page;
+use crate::{CaseReview, ReportConfig, ReviewDecision, ReviewState, ReviewSummary};
+use axum::extract::{Path, Request, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{AppendHeaders, Html, IntoResponse, Response};
 use axum::routing::post;
 use axum::{Json, Router, routing::get};
-use kompari::{DirDiffConfig, bless_image};
-use serde::Deserialize;
-use std::path::PathBuf;
-use std::sync::Arc;
+use futures_util::Stream;
+use kompari::{BlessJournal, DirDiffConfig, PairResult};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Component, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+
+/// How often the left and right directories are checked for changed images.
+///
+/// The directories are polled rather than watched with a filesystem notifier: each check
+/// lists all pairs again (walking the directories, or reading the manifest), but only pairs
+/// whose file size or modification time changed are compared.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Configuration of the review server.
+#[derive(Debug, Clone)]
+pub struct ReviewConfig {
+    address: SocketAddr,
+    read_only: bool,
+}
+
+impl Default for ReviewConfig {
+    /// Listens on `127.0.0.1:7200`.
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 7200),
+            read_only: false,
+        }
+    }
+}
+
+impl ReviewConfig {
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Address to listen on; other addresses than loopback make the server reachable
+    /// from the network.
+    pub fn set_bind_address(&mut self, value: IpAddr) {
+        self.address.set_ip(value);
+    }
+
+    pub fn set_port(&mut self, value: u16) {
+        self.address.set_port(value);
+    }
+
+    /// Serve the report without the possibility to accept images or change the review.
+    pub fn set_read_only(&mut self, value: bool) {
+        self.read_only = value;
+    }
+}
 
 struct AppState {
     report_config: ReportConfig,
+    session_token: SessionToken,
+    read_only: bool,
     diff_builder: DirDiffConfig,
+    /// Locked while images are blessed or restored, so operations do not interleave.
+    journal: Mutex<BlessJournal>,
+    review_state: Mutex<ReviewState>,
+    cache: Mutex<DiffCache>,
+    updates: broadcast::Sender<Vec<CaseUpdate>>,
+    shutdown: watch::Sender<bool>,
+    /// Numbers of images written by accepts of the session, so undone accepts can be subtracted.
+    accepted: Mutex<Vec<usize>>,
+    /// Start time of the server, so `ETag`s of images differ between runs.
+    started: u128,
+}
+
+impl AppState {
+    /// Decisions about cases that still differ, and the number of accepted cases.
+    fn summary(&self) -> ReviewSummary {
+        if let Err(e) = self.refresh() {
+            eprintln!("Failed to refresh images: {e}");
+        }
+        let mut summary = ReviewSummary {
+            accepted: self.accepted.lock().unwrap().iter().sum(),
+            ..ReviewSummary::default()
+        };
+        let review_state = self.review_state.lock().unwrap();
+        for name in self.cache.lock().unwrap().case_names() {
+            match review_state.get(name).map(|review| review.decision) {
+                Some(ReviewDecision::Rejected) => summary.rejected += 1,
+                Some(ReviewDecision::FollowUp) => summary.follow_up += 1,
+                _ => summary.pending += 1,
+            }
+        }
+        summary
+    }
+
+    /// Recompute changed pairs and send the changes to open pages.
+    fn refresh(&self) -> kompari::Result<()> {
+        let updates = self
+            .cache
+            .lock()
+            .unwrap()
+            .refresh(&self.diff_builder, &self.report_config)?;
+        if !updates.is_empty() {
+            // Sending fails only if no page is open
+            let _ = self.updates.send(updates);
+        }
+        Ok(())
+    }
+}
+
+/// Handle of a running review server.
+///
+/// The server runs in tasks of the tokio runtime in which it was started; the handle can be
+/// cloned and shared between tasks.
+#[derive(Debug, Clone)]
+pub struct ReviewServer {
+    address: SocketAddr,
+    session_token: SessionToken,
+    shutdown: watch::Sender<bool>,
+    finished: watch::Receiver<Option<ReviewSummary>>,
 }
 
+impl ReviewServer {
+    /// Start the server in the current tokio runtime.
+    ///
+    /// Port 0 in `review_config` binds an ephemeral port, see [`Self::address`].
+    pub async fn start(
+        diff_config: &DirDiffConfig,
+        report_config: &ReportConfig,
+        review_config: &ReviewConfig,
+    ) -> kompari::Result<Self> {
+        let listener = tokio::net::TcpListener::bind(review_config.address).await?;
+        let address = listener.local_addr()?;
+        let session_token = SessionToken::new(address.port())?;
+        // Accepted images would be written into the export of the revision
+        let read_only = review_config.read_only || diff_config.left_revision().is_some();
+        let mut report_config = report_config.clone();
+        report_config.set_review(!read_only);
+        report_config.serve_images = true;
+        report_config.session_token = Some(session_token.value().to_string());
+        let shutdown = watch::Sender::new(false);
+        let journal = diff_config.journal()?;
+        let shared_state = Arc::new(AppState {
+            report_config,
+            session_token: session_token.clone(),
+            read_only,
+            diff_builder: diff_config.clone(),
+            review_state: Mutex::new(ReviewState::load(ReviewState::path_for_journal(&journal))?),
+            journal: Mutex::new(journal),
+            cache: Mutex::default(),
+            updates: broadcast::channel(16).0,
+            shutdown: shutdown.clone(),
+            accepted: Mutex::default(),
+            started: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis()),
+        });
+        let state = shared_state.clone();
+        tokio::task::spawn_blocking(move || state.refresh())
+            .await
+            .map_err(|e| kompari::Error::GenericError(e.into()))??;
+
+        let app = Router::new()
+            .route("/", get(index))
+            .route("/update", post(update))
+            .route("/undo", post(undo))
+            .route("/finish", post(finish))
+            .route("/review", get(get_review).post(set_review))
+            .route("/events", get(events))
+            .route("/image/{kind}/{*path}", get(image))
+            .layer(axum::middleware::from_fn_with_state(
+                shared_state.clone(),
+                access_control,
+            ))
+            .with_state(shared_state.clone());
+        tokio::spawn(watch(Arc::downgrade(&shared_state)));
+        let (finished_sender, finished) = watch::channel(None);
+        let mut shutdown_receiver = shutdown.subscribe();
+        tokio::spawn(async move {
+            let result = axum::serve(listener, app)
+                .with_graceful_shutdown(async move {
+                    let _ = shutdown_receiver.wait_for(|shutdown| *shutdown).await;
+                })
+                .await;
+            if let Err(e) = result {
+                eprintln!("Review server failed: {e}");
+            }
+            let summary = tokio::task::spawn_blocking(move || shared_state.summary())
+                .await
+                .unwrap_or_default();
+            finished_sender.send_replace(Some(summary));
+        });
+        Ok(Self {
+            address,
+            session_token,
+            shutdown,
+            finished,
+        })
+    }
+
+    /// Address the server is bound to.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// URL of the review page with the session token required to access the server;
+    /// `localhost` is used if the server listens on all addresses.
+    pub fn url(&self) -> String {
+        let query = self.session_token.query();
+        if self.address.ip().is_unspecified() {
+            format!("http://localhost:{}/{query}", self.address.port())
+        } else {
+            format!("http://{}/{query}", self.address)
+        }
+    }
+
+    /// Stop the server; open pages are disconnected and [`Self::finished`] completes
+    /// when running requests are done.
+    ///
+    /// The server is also stopped by the "Finish review" button of the page.
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// Wait until the server stops and return the summary of the review.
+    pub async fn finished(&self) -> ReviewSummary {
+        let mut finished = self.finished.clone();
+        match finished.wait_for(Option::is_some).await {
+            Ok(summary) => summary.clone().unwrap_or_default(),
+            Err(_) => ReviewSummary::default(),
+        }
+    }
+}
+
+/// Run the review server until the review is finished; a tokio runtime is created for it.
 pub fn start_review_server(
-    diff_builder: &DirDiffConfig,
+    diff_config: &DirDiffConfig,
     report_config: &ReportConfig,
-    port: u16,
-) -> kompari::Result<()> {
-    let mut report_config = report_config.clone();
-    report_config.set_review(true);
-    report_config.set_embed_images(true);
-    let shared_state = Arc::new(AppState {
-        report_config,
-        diff_builder: diff_builder.clone(),
-    });
-    println!("Running at http://localhost:{port}");
+    review_config: &ReviewConfig,
+) -> kompari::Result<ReviewSummary> {
     tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()?
         .block_on(async {
-            let app = Router::new()
-                .route("/", get(index))
-                .route("/update", post(update))
-                .with_state(shared_state);
-            let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{port}"))
-                .await
-                .unwrap();
-            axum::serve(listener, app).await.unwrap();
-        });
-    Ok(())
+            let server = ReviewServer::start(diff_config, report_config, review_config).await?;
+            let address = server.address();
+            if !address.ip().is_loopback() {
+                eprintln!("Warning: the review server is reachable from the network at {address}");
+            }
+            if let Some(revision) = diff_config.left_revision() {
+                println!(
+                    "Left images are read from git revision `{revision}`, the review is read-only"
+                );
+            }
+            println!("Running at {}", server.url());
+            let summary = server.finished().await;
+            println!("Review finished: {summary}");
+            Ok(summary)
+        })
+}
+
+async fn access_control(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match state.session_token.check_request(&request, state.read_only) {
+        Ok(Access::Granted) => next.run(request).await,
+        // Store the token and remove it from the address bar
+        Ok(Access::GrantedByQuery) => (
+            StatusCode::SEE_OTHER,
+            [
+                (header::LOCATION, request.uri().path().to_string()),
+                (header::SET_COOKIE, state.session_token.set_cookie()),
+            ],
+        )
+            .into_response(),
+        Err(rejection) => rejection.into_response(),
+    }
 }
 
 fn result_to_response(result: kompari::Result<String>) -> (StatusCode, Html<String>) {
@@ -53,11 +298,71 @@ fn result_to_response(result: kompari::Result<String>) -> (StatusCode, Html<Stri
     }
 }
 
-async fn index(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    result_to_response((|| {
-        let diff = state.diff_builder.create_diff()?;
-        render_html_report(&state.report_config, diff.results())
-    })())
+async fn index(State(state): State<Arc<AppState>>) -> Response {
+    run_blocking(state, AppState::render_index).await
+}
+
+/// Serves `/image/left/{name}`, `/image/right/{name}` and `/image/diff/{name}/{method}`.
+///
+/// The version of the case is used as `ETag`, so browsers reuse images until they change.
+/// Difference images are encoded once for each version.
+async fn image(
+    State(state): State<Arc<AppState>>,
+    Path((kind, path)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Response {
+    let Some((kind, name)) = ImageKind::parse(&kind, &path) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let name = name.to_string();
+    let if_none_match = headers.get(header::IF_NONE_MATCH).cloned();
+    run_blocking(state, move |state| {
+        state.image(&kind, &name, if_none_match.as_ref())
+    })
+    .await
+}
+
+/// Poll image directories every [`WATCH_INTERVAL`] and push changed cases to open pages,
+/// until the server state is dropped.
+async fn watch(state: Weak<AppState>) {
+    let mut interval = tokio::time::interval(WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let Some(state) = state.upgrade() else {
+            return;
+        };
+        match tokio::task::spawn_blocking(move || state.refresh()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("Failed to refresh images: {e}"),
+            Err(e) => eprintln!("Failed to refresh images: {e}"),
+        }
+    }
+}
+
+async fn events(
+    State(state): State<Arc<AppState>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.updates.subscribe();
+    let shutdown = state.shutdown.subscribe();
+    // The stream ends on shutdown, otherwise open pages would keep the server running
+    let stream = futures_util::stream::unfold(
+        (receiver, shutdown),
+        |(mut receiver, mut shutdown)| async move {
+            let event = tokio::select! {
+                update = receiver.recv() => match update {
+                    Ok(updates) => Event::default().event("cases").json_data(updates).unwrap(),
+                    // Some updates were missed, the page has to be loaded again
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        Event::default().event("reload").data("")
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+                _ = shutdown.wait_for(|shutdown| *shutdown) => return None,
+            };
+            Some((Ok(event), (receiver, shutdown)))
+        },
+    );
+    Sse::new(stream).keep_alive(KeepAlive::default())
 }
 
 #[derive(Deserialize, Debug)]
@@ -65,26 +370,266 @@ struct UpdateParams {
     accepted_names: Vec<String>,
 }
 
-async fn update(
+/// Response to a request with names that are not cases of the review.
+#[derive(Serialize, Debug)]
+struct RejectedNames {
+    error: &'static str,
+    rejected: Vec<String>,
+}
+
+impl IntoResponse for RejectedNames {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, Json(self)).into_response()
+    }
+}
+
+/// Lexically normalized absolute path, so it can be compared by prefix.
+fn normalize_path(path: &std::path::Path) -> std::io::Result<PathBuf> {
+    let mut result = PathBuf::new();
+    for component in std::path::absolute(path)?.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            component => result.push(component),
+        }
+    }
+    Ok(result)
+}
+
+impl AppState {
+    /// Find reported cases of the given names.
+    ///
+    /// Only names of cases in the current diff are accepted, so images are written only to
+    /// left paths of listed pairs. With a left directory (i.e. without a manifest), the left
+    /// path of a case also has to lie inside it. The check is lexical, so a symbolic link
+    /// inside the directory can still point outside of it.
+    fn find_cases(
+        &self,
+        names: &[String],
+    ) -> kompari::Result<Result<Vec<Arc<PairResult>>, RejectedNames>> {
+        self.refresh()?;
+        let left_dir = self.diff_builder.left_path();
+        let left_dir = if left_dir.as_os_str().is_empty() {
+            None
+        } else {
+            Some(normalize_path(left_dir)?)
+        };
+        let cache = self.cache.lock().unwrap();
+        let mut cases = Vec::new();
+        let mut rejected = Vec::new();
+        for name in names {
+            let Some(case) = cache.get(name) else {
+                rejected.push(name.clone());
+                continue;
+            };
+            let result = case.result().clone();
+            let inside = match &left_dir {
+                Some(left_dir) => normalize_path(&result.left)?.starts_with(left_dir),
+                None => true,
+            };
+            if inside {
+                cases.push(result);
+            } else {
+                rejected.push(name.clone());
+            }
+        }
+        Ok(if rejected.is_empty() {
+            Ok(cases)
+        } else {
+            Err(RejectedNames {
+                error: "Unknown cases",
+                rejected,
+            })
+        })
+    }
+
+    fn render_index(&self) -> (StatusCode, Html<String>) {
+        result_to_response(self.refresh().map(|()| {
+            let cache = self.cache.lock().unwrap();
+            render_page(
+                &self.report_config,
+                cache.n_cases(),
+                vec![cache.render()],
+                true,
+            )
+        }))
+    }
+
+    /// Bless images of accepted cases.
+    fn accept(&self, names: Vec<String>) -> Response {
+        let accepted = match self.find_cases(&names) {
+            Ok(Ok(cases)) => cases,
+            Ok(Err(rejected)) => return rejected.into_response(),
+            Err(e) => {
+                eprintln!("Failed to find accepted cases: {e}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        for pair in &accepted {
+            println!(
+                "Updating {} -> {}",
+                pair.right.display(),
+                pair.left.display()
+            );
+        }
+        let images: Vec<_> = accepted
+            .iter()
+            .map(|pair| (pair.right.as_path(), pair.left.as_path()))
+            .collect();
+        // Previous reviews are stored in the journal, so undo restores them with the images.
+        // The review state is not locked while blessing, so reviews can be read meanwhile.
+        let snapshot = self.review_state.lock().unwrap().snapshot(&names);
+        let journal = self.journal.lock().unwrap();
+        let n_written = match journal.bless_with_data(&images, Some(&snapshot)) {
+            Ok(n_written) => n_written,
+            Err(e) => {
+                eprintln!("Failed to update images: {e}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        self.accepted.lock().unwrap().push(n_written);
+        drop(journal);
+        let mut review_state = self.review_state.lock().unwrap();
+        review_state.mark_blessed(&names);
+        if let Err(e) = review_state.save() {
+            eprintln!("Failed to save review state: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+        StatusCode::OK.into_response()
+    }
+
+    fn image(&self, kind: &ImageKind, name: &str, if_none_match: Option<&HeaderValue>) -> Response {
+        // Refreshes hold the cache while changed pairs are compared
+        let Some(case) = self.cache.lock().unwrap().get(name) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        let etag = format!("\"{}-{}\"", self.started, case.id());
+        if if_none_match.is_some_and(|value| value.as_bytes() == etag.as_bytes()) {
+            return StatusCode::NOT_MODIFIED.into_response();
+        }
+        match case.image(kind, self.report_config.size_optimization) {
+            Ok(image) => {
+                let mut headers = vec![(header::CONTENT_TYPE, "image/png".to_string())];
+                // An image that changed after the comparison is served without the `ETag`
+                // of the compared version, so it is not reused once the case is refreshed
+                if image.is_current {
+                    headers.push((header::CACHE_CONTROL, "no-cache".to_string()));
+                    headers.push((header::ETAG, etag));
+                } else {
+                    headers.push((header::CACHE_CONTROL, "no-store".to_string()));
+                }
+                (AppendHeaders(headers), image.data).into_response()
+            }
+            Err(e) => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+        }
+    }
+
+    fn set_review(&self, params: ReviewParams) -> Response {
+        match self.find_cases(std::slice::from_ref(&params.name)) {
+            Ok(Ok(_)) => {}
+            Ok(Err(rejected)) => return rejected.into_response(),
+            Err(e) => {
+                eprintln!("Failed to find reviewed case: {e}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        }
+        let mut review_state = self.review_state.lock().unwrap();
+        review_state.set(
+            params.name,
+            CaseReview {
+                decision: params.decision,
+                comment: params.comment,
+            },
+        );
+        if let Err(e) = review_state.save() {
+            eprintln!("Failed to save review state: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+        StatusCode::OK.into_response()
+    }
+
+    fn undo(&self) -> StatusCode {
+        match self.journal.lock().unwrap().undo() {
+            Ok(Some(undone)) => {
+                for path in &undone.paths {
+                    println!("Restored {}", path.display());
+                }
+                // The undone operation may come from before the session
+                self.accepted.lock().unwrap().pop();
+                if let Some(snapshot) = &undone.data {
+                    let mut review_state = self.review_state.lock().unwrap();
+                    if let Err(e) = review_state
+                        .restore(snapshot)
+                        .and_then(|()| review_state.save())
+                    {
+                        eprintln!("Failed to restore review state: {e}");
+                        return StatusCode::INTERNAL_SERVER_ERROR;
+                    }
+                }
+                StatusCode::OK
+            }
+            Ok(None) => StatusCode::CONFLICT,
+            Err(e) => {
+                eprintln!("Failed to undo: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+async fn update(State(state): State<Arc<AppState>>, Json(params): Json<UpdateParams>) -> Response {
+    run_blocking(state, move |state| state.accept(params.accepted_names)).await
+}
+
+async fn get_review(State(state): State<Arc<AppState>>) -> Response {
+    run_blocking(state, |state| {
+        Json(state.review_state.lock().unwrap().cases().clone())
+    })
+    .await
+}
+
+#[derive(Deserialize, Debug)]
+struct ReviewParams {
+    name: String,
+    decision: ReviewDecision,
+    #[serde(default)]
+    comment: String,
+}
+
+async fn set_review(
     State(state): State<Arc<AppState>>,
-    Json(params): Json<UpdateParams>,
-) -> StatusCode {
-    let paths: Vec<_> = params
-        .accepted_names
-        .into_iter()
-        .map(PathBuf::from)
-        .collect();
-    if paths.iter().any(|p| !p.is_relative()) {
-        return StatusCode::BAD_REQUEST;
-    }
-    for path in paths {
-        let left = state.diff_builder.left_path().join(&path);
-        let right = state.diff_builder.right_path().join(&path);
-        println!("Updating {} -> {}", right.display(), left.display());
-        if let Err(e) = bless_image(&right, &left) {
-            eprintln!("Failed to rename {}: {}", right.display(), e);
-            return StatusCode::INTERNAL_SERVER_ERROR;
-        }
-    }
-    StatusCode::OK
+    Json(params): Json<ReviewParams>,
+) -> Response {
+    run_blocking(state, move |state| state.set_review(params)).await
+}
+
+async fn undo(State(state): State<Arc<AppState>>) -> Response {
+    run_blocking(state, AppState::undo).await
+}
+
+/// Stop the server and return the summary of the review.
+async fn finish(State(state): State<Arc<AppState>>) -> Response {
+    run_blocking(state, |state| {
+        let summary = state.summary();
+        state.shutdown.send_replace(true);
+        Json(summary)
+    })
+    .await
+}
+
+/// Run `f` on the blocking thread pool, so diffs and file operations do not block
+/// the runtime in which the server runs.
+async fn run_blocking<T: IntoResponse + Send + 'static>(
+    state: Arc<AppState>,
+    f: impl FnOnce(&AppState) -> T + Send + 'static,
+) -> Response {
+    match tokio::task::spawn_blocking(move || f(&state)).await {
+        Ok(response) => response.into_response(),
+        Err(e) => {
+            eprintln!("Request failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
 }
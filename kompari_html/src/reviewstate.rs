@@ -0,0 +1,167 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use kompari::{BlessJournal, create_ignored_dir};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Decision made about a case in the review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReviewDecision {
+    #[default]
+    Pending,
+    Accepted,
+    Rejected,
+    /// The case needs a follow-up before it can be accepted or rejected.
+    FollowUp,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaseReview {
+    pub decision: ReviewDecision,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub comment: String,
+}
+
+/// Decisions and comments of a review, persisted in a JSON file,
+/// so a review can span multiple sessions of the review server.
+///
+/// The state is local to a working copy and is not meant to be committed.
+#[derive(Debug, Clone, Default)]
+pub struct ReviewState {
+    path: PathBuf,
+    cases: BTreeMap<String, CaseReview>,
+}
+
+impl ReviewState {
+    /// Load the state from `path`; an empty state is created if the file does not exist.
+    pub fn load(path: PathBuf) -> kompari::Result<Self> {
+        let cases = if path.is_file() {
+            serde_json::from_str(&std::fs::read_to_string(&path)?).map_err(|e| {
+                kompari::Error::GenericError(
+                    format!("Invalid review file `{}`: {e}", path.display()).into(),
+                )
+            })?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { path, cases })
+    }
+
+    /// Path of the review file in the [`BlessJournal`] directory of reference images,
+    /// e.g. `tests/.snapshots.kompari-journal/review.json` for `tests/snapshots`;
+    /// the directory is ignored by git.
+    pub fn path_for_target_dir(target_dir: &Path) -> PathBuf {
+        Self::path_for_journal(&BlessJournal::for_target_dir(target_dir))
+    }
+
+    /// Path of the review file in the directory of `journal`.
+    pub fn path_for_journal(journal: &BlessJournal) -> PathBuf {
+        journal.dir().join("review.json")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn cases(&self) -> &BTreeMap<String, CaseReview> {
+        &self.cases
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CaseReview> {
+        self.cases.get(name)
+    }
+
+    /// Set the review of a case; pending cases without a comment are removed from the state.
+    pub fn set(&mut self, name: String, review: CaseReview) {
+        if review == CaseReview::default() {
+            self.cases.remove(&name);
+        } else {
+            self.cases.insert(name, review);
+        }
+    }
+
+    /// Reviews of the given cases serialized for [`Self::restore`], e.g. to store them
+    /// with a bless operation in the journal.
+    pub fn snapshot(&self, names: &[String]) -> String {
+        let reviews: BTreeMap<&str, CaseReview> = names
+            .iter()
+            .map(|name| (name.as_str(), self.get(name).cloned().unwrap_or_default()))
+            .collect();
+        serde_json::to_string(&reviews).unwrap()
+    }
+
+    /// Restore reviews from [`Self::snapshot`].
+    pub fn restore(&mut self, snapshot: &str) -> kompari::Result<()> {
+        let reviews: BTreeMap<String, CaseReview> =
+            serde_json::from_str(snapshot).map_err(|e| {
+                kompari::Error::GenericError(format!("Invalid review data: {e}").into())
+            })?;
+        for (name, review) in reviews {
+            self.set(name, review);
+        }
+        Ok(())
+    }
+
+    /// Reset decisions of blessed cases, as they must not apply to future differences;
+    /// comments are kept.
+    pub fn mark_blessed(&mut self, names: &[String]) {
+        for name in names {
+            if let Some(review) = self.cases.get(name) {
+                let review = CaseReview {
+                    decision: ReviewDecision::Pending,
+                    comment: review.comment.clone(),
+                };
+                self.set(name.clone(), review);
+            }
+        }
+    }
+
+    /// Write the state into its file; the file is removed if the state is empty.
+    pub fn save(&self) -> kompari::Result<()> {
+        if self.cases.is_empty() {
+            if self.path.is_file() {
+                std::fs::remove_file(&self.path)?;
+            }
+            return Ok(());
+        }
+        let data = serde_json::to_string_pretty(&self.cases)
+            .map_err(|e| kompari::Error::GenericError(e.into()))?;
+        if let Some(parent) = self.path.parent() {
+            create_ignored_dir(parent)?;
+        }
+        std::fs::write(&self.path, data)?;
+        Ok(())
+    }
+}
+
+/// Outcome of a review session.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReviewSummary {
+    /// Cases accepted (blessed) during the session.
+    pub accepted: usize,
+    pub rejected: usize,
+    pub follow_up: usize,
+    /// Remaining cases without a decision, including cases marked as accepted
+    /// that were not blessed.
+    pub pending: usize,
+}
+
+impl ReviewSummary {
+    /// Number of cases whose images still differ.
+    pub fn n_unresolved(&self) -> usize {
+        self.rejected + self.follow_up + self.pending
+    }
+}
+
+impl std::fmt::Display for ReviewSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} accepted, {} rejected, {} needing follow-up, {} pending",
+            self.accepted, self.rejected, self.follow_up, self.pending
+        )
+    }
+}
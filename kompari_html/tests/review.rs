@@ -0,0 +1,141 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use kompari::DirDiffConfig;
+use kompari_html::{ReportConfig, ReviewConfig, ReviewServer};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+async fn send(address: SocketAddr, head: &str) -> String {
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    let request = format!("{head}\r\nHost: {address}\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    // Bodies of images are not text, but headers are
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+/// Session token from the URL printed by the server.
+fn token(server: &ReviewServer) -> String {
+    server.url().split_once("?token=").unwrap().1.to_string()
+}
+
+async fn get(server: &ReviewServer, path: &str) -> String {
+    let head = format!("GET {path} HTTP/1.1\r\nX-Kompari-Token: {}", token(server));
+    send(server.address(), &head).await
+}
+
+async fn start_server() -> ReviewServer {
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("tests");
+    let diff_config = DirDiffConfig::new(test_dir.join("left"), test_dir.join("right"));
+    let mut review_config = ReviewConfig::default();
+    review_config.set_port(0);
+    ReviewServer::start(&diff_config, &ReportConfig::default(), &review_config)
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_review_server_shutdown() {
+    let server = start_server().await;
+    let address = server.address();
+    assert_ne!(address.port(), 0);
+    assert!(address.ip().is_loopback());
+
+    let response = send(address, "GET / HTTP/1.1").await;
+    assert!(response.starts_with("HTTP/1.1 403"));
+    let response = send(address, &format!("GET /?token={} HTTP/1.1", token(&server))).await;
+    assert!(response.starts_with("HTTP/1.1 303"));
+    assert!(response.contains(&format!("kompari-token-{}=", address.port())));
+
+    let page = get(&server, "/").await;
+    assert!(page.starts_with("HTTP/1.1 200"));
+    assert!(page.contains("bright.png"));
+
+    // An open page must not prevent the shutdown
+    let mut events = TcpStream::connect(address).await.unwrap();
+    let request = format!(
+        "GET /events HTTP/1.1\r\nHost: {address}\r\nX-Kompari-Token: {}\r\n\r\n",
+        token(&server)
+    );
+    events.write_all(request.as_bytes()).await.unwrap();
+    let mut buffer = [0; 64];
+    assert!(events.read(&mut buffer).await.unwrap() > 0);
+
+    server.shutdown();
+    tokio::time::timeout(Duration::from_secs(10), server.finished())
+        .await
+        .expect("Server did not finish after shutdown");
+    assert!(TcpStream::connect(address).await.is_err());
+}
+
+// The server must also work in a runtime with a single thread
+#[tokio::test]
+async fn test_review_server_finish() {
+    let server = start_server().await;
+    let address = server.address();
+    let token = token(&server);
+    let response = send(
+        address,
+        &format!("POST /finish HTTP/1.1\r\nX-Kompari-Token: {token}\r\nContent-Length: 0"),
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains(r#""accepted":0"#));
+
+    let summary = tokio::time::timeout(Duration::from_secs(10), server.finished())
+        .await
+        .expect("Server did not finish after the review was finished");
+    assert_eq!(summary.accepted, 0);
+    assert!(summary.pending > 0);
+    assert_eq!(summary.n_unresolved(), summary.pending);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_review_server_images() {
+    let server = start_server().await;
+    let address = server.address();
+
+    let response = get(&server, "/image/left/bright.png").await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("content-type: image/png"));
+    let etag = response
+        .lines()
+        .find_map(|line| line.strip_prefix("etag: "))
+        .expect("Image has no ETag")
+        .to_string();
+
+    let head = format!(
+        "GET /image/right/bright.png HTTP/1.1\r\nX-Kompari-Token: {}\r\nIf-None-Match: {etag}",
+        token(&server)
+    );
+    assert!(send(address, &head).await.starts_with("HTTP/1.1 304"));
+
+    // The encoded difference image is cached, so both responses are the same
+    let diff = get(&server, "/image/diff/bright.png/RedGreen").await;
+    assert!(diff.starts_with("HTTP/1.1 200"));
+    assert!(diff.contains(&format!("etag: {etag}")));
+    let body = |response: &str| response.split_once("\r\n\r\n").unwrap().1.to_string();
+    let again = get(&server, "/image/diff/bright.png/RedGreen").await;
+    assert_eq!(body(&again), body(&diff));
+
+    for path in [
+        "/image/diff/bright.png/Unknown",
+        "/image/diff/bright.png",
+        "/image/other/bright.png",
+        "/image/left/unknown.png",
+    ] {
+        assert!(
+            get(&server, path).await.starts_with("HTTP/1.1 404"),
+            "{path}"
+        );
+    }
+    server.shutdown();
+}
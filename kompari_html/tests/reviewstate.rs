@@ -0,0 +1,78 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use kompari_html::{CaseReview, ReviewDecision, ReviewState};
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn test_review_state_persistence() {
+    assert_eq!(
+        ReviewState::path_for_target_dir(Path::new("tests/snapshots")),
+        Path::new("tests/.snapshots.kompari-journal/review.json")
+    );
+    let workdir = TempDir::new().unwrap();
+    let path = workdir.path().join("review.json");
+    let mut state = ReviewState::load(path.clone()).unwrap();
+    assert!(state.cases().is_empty());
+    state.set(
+        "bright.png".to_string(),
+        CaseReview {
+            decision: ReviewDecision::FollowUp,
+            comment: "Check the gamma".to_string(),
+        },
+    );
+    state.set(
+        "shift.png".to_string(),
+        CaseReview {
+            decision: ReviewDecision::Rejected,
+            comment: String::new(),
+        },
+    );
+    state.save().unwrap();
+
+    let mut state = ReviewState::load(path.clone()).unwrap();
+    assert_eq!(state.cases().len(), 2);
+    assert_eq!(
+        state.get("bright.png").unwrap().decision,
+        ReviewDecision::FollowUp
+    );
+    assert_eq!(state.get("bright.png").unwrap().comment, "Check the gamma");
+
+    // Pending cases without a comment are not stored
+    state.set("bright.png".to_string(), CaseReview::default());
+    state.set("shift.png".to_string(), CaseReview::default());
+    state.save().unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_review_state_blessed() {
+    let workdir = TempDir::new().unwrap();
+    let path = workdir.path().join("state").join("review.json");
+    let mut state = ReviewState::load(path.clone()).unwrap();
+    let review = CaseReview {
+        decision: ReviewDecision::Accepted,
+        comment: "Expected after the font change".to_string(),
+    };
+    state.set("bright.png".to_string(), review.clone());
+    let names = ["bright.png".to_string(), "shift.png".to_string()];
+    let snapshot = state.snapshot(&names);
+
+    // Decisions are reset, comments are kept
+    state.mark_blessed(&names);
+    assert_eq!(
+        state.get("bright.png").unwrap(),
+        &CaseReview {
+            decision: ReviewDecision::Pending,
+            comment: review.comment.clone(),
+        }
+    );
+    assert!(state.get("shift.png").is_none());
+    state.save().unwrap();
+    assert!(workdir.path().join("state/.gitignore").is_file());
+
+    state.restore(&snapshot).unwrap();
+    assert_eq!(state.get("bright.png").unwrap(), &review);
+    assert!(state.get("shift.png").is_none());
+}
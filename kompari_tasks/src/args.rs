@@ -3,6 +3,8 @@
 
 use clap::{Parser, ValueEnum};
 use kompari::SizeOptimizationLevel;
+use kompari_html::ReviewConfig;
+use std::net::IpAddr;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -19,6 +21,11 @@ pub enum Command {
     Clean,
     DeadSnapshots(DeadSnapshotArgs),
     SizeCheck(SizeCheckArgs),
+    Show(ShowArgs),
+    Check(CheckArgs),
+    Bless(BlessArgs),
+    /// Undo the last bless operation
+    Undo,
 }
 
 #[derive(ValueEnum, Debug, Clone, Copy)]
@@ -39,19 +46,55 @@ impl SizeOptimization {
     }
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[clap(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Html,
+    /// Machine-readable results without diff images
+    Json,
+    /// Compact summary table, e.g. for pull request comments
+    Markdown,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Json => "json",
+            Self::Markdown => "md",
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 pub struct ReportArgs {
     /// Output filename
     #[arg(long)]
     pub output: Option<PathBuf>,
 
-    /// Embed images into the report
+    /// Embed images into the report; always done with a left revision
     #[arg(long, default_value_t = false)]
     pub embed_images: bool,
 
     /// Optimize image sizes in HTML report
     #[arg(long, default_value = "none")]
     pub optimize_size: SizeOptimization,
+
+    /// Format of the report
+    #[arg(long, default_value = "html")]
+    pub format: ReportFormat,
+
+    /// Write diff images next to a Markdown report and link them from it
+    #[arg(long, default_value_t = false)]
+    pub markdown_images: bool,
+
+    /// Also write results as `JUnit` XML into the given file
+    #[arg(long)]
+    pub junit: Option<PathBuf>,
+
+    /// Exit with the code of `check` (e.g. 1 if images differ) instead of 0
+    #[arg(long, default_value_t = false)]
+    pub exit_code: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -60,11 +103,89 @@ pub struct ReviewArgs {
     #[arg(long, default_value_t = 7200)]
     pub port: u16,
 
+    /// Address for web server; use e.g. 0.0.0.0 to make it reachable from the network
+    #[arg(long, default_value = "127.0.0.1")]
+    pub bind: IpAddr,
+
+    /// Serve the report without the possibility to accept images
+    #[arg(long)]
+    pub read_only: bool,
+
     /// Optimize image sizes in generated HTML
     #[arg(long, default_value = "none")]
     pub optimize_size: SizeOptimization,
 }
 
+impl ReviewArgs {
+    pub fn review_config(&self) -> ReviewConfig {
+        let mut config = ReviewConfig::default();
+        config.set_bind_address(self.bind);
+        config.set_port(self.port);
+        config.set_read_only(self.read_only);
+        config
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[clap(rename_all = "lowercase")]
+pub enum GraphicsProtocol {
+    /// Detect the protocol from the terminal
+    Auto,
+    /// Text only
+    None,
+    Kitty,
+    Iterm,
+    Sixel,
+}
+
+#[derive(Parser, Debug)]
+pub struct ShowArgs {
+    /// Inline graphics protocol used for thumbnails
+    #[arg(long, default_value = "auto")]
+    pub graphics: GraphicsProtocol,
+
+    /// Height of thumbnails in pixels
+    #[arg(long, default_value_t = 96)]
+    pub thumbnail_height: u32,
+}
+
+#[derive(Parser, Debug)]
+pub struct CheckArgs {
+    /// Write an HTML report with embedded images into the given file if the check fails
+    #[arg(long)]
+    pub report_on_failure: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[clap(rename_all = "kebab-case")]
+pub enum BlessStatus {
+    /// The image exists only on the right side
+    New,
+    /// The image content differs
+    Changed,
+    /// The image size differs
+    SizeMismatch,
+}
+
+#[derive(Parser, Debug)]
+pub struct BlessArgs {
+    /// Bless only images whose name matches the glob (can be repeated)
+    #[arg(long)]
+    pub name: Vec<String>,
+
+    /// Bless only images with the given status (can be repeated)
+    #[arg(long, value_delimiter = ',')]
+    pub status: Vec<BlessStatus>,
+
+    /// Bless all new and different images
+    #[arg(long, default_value_t = false, conflicts_with_all = ["name", "status"])]
+    pub all: bool,
+
+    /// Only list images that would be blessed
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
 #[derive(Parser, Debug)]
 pub struct DeadSnapshotArgs {
     #[arg(long, default_value_t = false)]
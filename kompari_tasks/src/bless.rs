@@ -0,0 +1,138 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::args::{BlessArgs, BlessStatus};
+use globset::{GlobBuilder, GlobSetBuilder};
+use kompari::{BlessJournal, DirDiffConfig, ImageDifference, PairResult};
+use kompari_html::ReviewState;
+use std::io::Write;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+impl BlessStatus {
+    /// Status of a pair that can be blessed; `None` for matching images,
+    /// images missing on the right side and loading errors.
+    pub fn of_pair(pair: &PairResult) -> Option<Self> {
+        match &pair.image_diff {
+            Ok(ImageDifference::None) => None,
+            Ok(ImageDifference::Content { .. }) => Some(Self::Changed),
+            Ok(ImageDifference::SizeMismatch { .. }) => Some(Self::SizeMismatch),
+            Err(e) if e.is_left_missing() && e.right().is_none() => Some(Self::New),
+            Err(_) => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Changed => "changed",
+            Self::SizeMismatch => "size mismatch",
+        }
+    }
+}
+
+/// Select pairs to bless by name globs and statuses.
+pub fn select_pairs_to_bless<'a>(
+    results: &'a [PairResult],
+    args: &BlessArgs,
+) -> kompari::Result<Vec<(&'a PairResult, BlessStatus)>> {
+    if !args.all && args.name.is_empty() && args.status.is_empty() {
+        return Err(kompari::Error::GenericError(
+            "Select images to bless with --name, --status or --all".into(),
+        ));
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in &args.name {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| kompari::Error::InvalidPattern(e.to_string()))?;
+        builder.add(glob);
+    }
+    let names = builder
+        .build()
+        .map_err(|e| kompari::Error::InvalidPattern(e.to_string()))?;
+    Ok(results
+        .iter()
+        .filter_map(|pair| BlessStatus::of_pair(pair).map(|status| (pair, status)))
+        .filter(|(pair, status)| {
+            (args.name.is_empty() || names.is_match(&pair.title))
+                && (args.status.is_empty() || args.status.contains(status))
+        })
+        .collect())
+}
+
+/// Returns an error if left images are read from a git revision, as blessed images
+/// would be written into a temporary export instead of the working tree.
+pub fn require_left_working_tree(diff_config: &DirDiffConfig) -> kompari::Result<()> {
+    match diff_config.left_revision() {
+        Some(revision) => Err(kompari::Error::GenericError(
+            format!(
+                "Left images are read from git revision `{revision}`, \
+                 images cannot be blessed or restored in this mode"
+            )
+            .into(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Copy selected right images over left images, or only list them with `--dry-run`.
+///
+/// The operation is recorded in the [`BlessJournal`] of the left directory and can be undone.
+pub fn run_bless(diff_config: &DirDiffConfig, args: &BlessArgs) -> kompari::Result<()> {
+    if !args.dry_run {
+        require_left_working_tree(diff_config)?;
+    }
+    let diff = diff_config.create_diff()?;
+    let selected = select_pairs_to_bless(diff.results(), args)?;
+    let stdout = termcolor::StandardStream::stdout(termcolor::ColorChoice::Auto);
+    let mut stdout = stdout.lock();
+    for (pair, status) in &selected {
+        stdout.set_color(ColorSpec::new().set_fg(None))?;
+        write!(stdout, "{}: ", pair.title)?;
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))?;
+        writeln!(stdout, "{}", status.label())?;
+    }
+    stdout.set_color(ColorSpec::new().set_fg(None))?;
+    if args.dry_run {
+        writeln!(stdout, "{} image(s) would be blessed", selected.len())?;
+    } else {
+        let images: Vec<_> = selected
+            .iter()
+            .map(|(pair, _)| (pair.right.as_path(), pair.left.as_path()))
+            .collect();
+        let n_written = if images.is_empty() {
+            0
+        } else {
+            diff_config.journal()?.bless(&images)?
+        };
+        writeln!(stdout, "{n_written} image(s) blessed")?;
+        if n_written < images.len() {
+            writeln!(
+                stdout,
+                "{} image(s) left untouched as their pixels did not change",
+                images.len() - n_written
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Undo the last bless operation recorded in `journal`.
+pub fn run_undo(journal: &BlessJournal) -> kompari::Result<()> {
+    match journal.undo()? {
+        Some(undone) => {
+            for path in &undone.paths {
+                println!("Restored {}", path.display());
+            }
+            // Reviews of cases accepted in the review server
+            if let Some(snapshot) = &undone.data {
+                let mut review_state = ReviewState::load(ReviewState::path_for_journal(journal))?;
+                review_state.restore(snapshot)?;
+                review_state.save()?;
+            }
+        }
+        None => println!("Nothing to undo"),
+    }
+    Ok(())
+}
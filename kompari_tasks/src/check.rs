@@ -0,0 +1,111 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::args::{CheckArgs, GraphicsProtocol};
+use crate::show::print_pair;
+use kompari::{DirDiffConfig, ImageDifference, PairResult};
+use kompari_html::{ReportConfig, render_html_report};
+use std::io::Write;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+/// Outcome of a check; a more severe outcome has a higher exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    /// All images match.
+    Match,
+    /// Some images differ in content or size.
+    Differences,
+    /// Some images are missing on one side.
+    MissingFiles,
+    /// Some images could not be loaded.
+    LoadErrors,
+}
+
+/// Process exit code for errors (e.g. an invalid glob, manifest or I/O error),
+/// distinct from exit codes of [`CheckStatus`].
+pub const ERROR_EXIT_CODE: u8 = 4;
+
+impl CheckStatus {
+    /// Process exit code: 0 for match, 1 for differences, 2 for missing files, 3 for load errors.
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Self::Match => 0,
+            Self::Differences => 1,
+            Self::MissingFiles => 2,
+            Self::LoadErrors => 3,
+        }
+    }
+
+    fn of_pair(pair: &PairResult) -> Self {
+        match &pair.image_diff {
+            Ok(ImageDifference::None) => Self::Match,
+            Ok(_) => Self::Differences,
+            Err(e) if e.is_left_missing() || e.is_right_missing() => Self::MissingFiles,
+            Err(_) => Self::LoadErrors,
+        }
+    }
+
+    /// The most severe status of all pairs.
+    pub fn of_results(results: &[PairResult]) -> Self {
+        results
+            .iter()
+            .map(Self::of_pair)
+            .max()
+            .unwrap_or(Self::Match)
+    }
+}
+
+/// Compare images, print a summary and optionally write a report if the check fails.
+///
+/// The caller is expected to exit with [`CheckStatus::exit_code`], or [`ERROR_EXIT_CODE`]
+/// if an error is returned.
+pub fn run_check(
+    diff_config: &DirDiffConfig,
+    report_config: &ReportConfig,
+    args: &CheckArgs,
+) -> kompari::Result<CheckStatus> {
+    let diff = diff_config.create_diff()?;
+    let stdout = termcolor::StandardStream::stdout(termcolor::ColorChoice::Auto);
+    let mut stdout = stdout.lock();
+    for pair in diff.results() {
+        print_pair(
+            &mut stdout,
+            pair,
+            GraphicsProtocol::None,
+            0,
+            diff_config.resolve_lfs(),
+        )?;
+    }
+    let status = CheckStatus::of_results(diff.results());
+    if status == CheckStatus::Match {
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::Green)))?;
+        writeln!(stdout, "All images match")?;
+        stdout.set_color(ColorSpec::new().set_fg(None))?;
+        return Ok(status);
+    }
+    writeln!(stdout, "----------------------------")?;
+    for (status, label) in [
+        (CheckStatus::Differences, "Different"),
+        (CheckStatus::MissingFiles, "Missing"),
+        (CheckStatus::LoadErrors, "Loading errors"),
+    ] {
+        let count = diff
+            .results()
+            .iter()
+            .filter(|pair| CheckStatus::of_pair(pair) == status)
+            .count();
+        let color = if count > 0 { Color::Red } else { Color::Green };
+        write!(stdout, "{label}: ")?;
+        stdout.set_color(ColorSpec::new().set_fg(Some(color)))?;
+        writeln!(stdout, "{count}")?;
+        stdout.set_color(ColorSpec::new().set_fg(None))?;
+    }
+    if let Some(output) = &args.report_on_failure {
+        let mut report_config = report_config.clone();
+        report_config.set_embed_images(true);
+        let report = render_html_report(&report_config, diff.results())?;
+        std::fs::write(output, report)?;
+        writeln!(stdout, "Report written into '{}'", output.display())?;
+    }
+    Ok(status)
+}
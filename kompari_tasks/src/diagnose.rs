@@ -0,0 +1,247 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use kompari::DirDiffConfig;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use termcolor::{Color, ColorSpec, WriteColor};
+use walkdir::WalkDir;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const LFS_HEADER: &[u8] = b"version https://git-lfs.github.com/spec/v1";
+const N_EXAMPLES: usize = 3;
+/// Maximal number of images that are fully decoded to find corrupted files.
+const N_DECODED: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputProblemKind {
+    /// A directory (or its part) could not be read.
+    UnreadableDirectory,
+    /// The file is a Git LFS pointer whose object is not available.
+    LfsPointer,
+    /// The file could not be read or does not look like a PNG image.
+    NotPng,
+    /// The file starts as a PNG image but cannot be decoded, e.g. it is truncated.
+    CorruptPng,
+}
+
+impl InputProblemKind {
+    fn describe(self) -> &'static str {
+        match self {
+            Self::UnreadableDirectory => "unreadable directories",
+            Self::LfsPointer => "unresolved Git LFS pointers",
+            Self::NotPng => "not readable PNG images",
+            Self::CorruptPng => "corrupted PNG images",
+        }
+    }
+
+    fn hint(self) -> &'static str {
+        match self {
+            Self::UnreadableDirectory => "Check that the directories exist and can be read.",
+            Self::LfsPointer => {
+                "Install Git LFS (https://git-lfs.com/) and run `git lfs pull`. If the objects \
+                 are already fetched, enable resolving of LFS pointers from the local storage."
+            }
+            Self::NotPng | Self::CorruptPng => {
+                "Check that the images were written completely and are stored as PNG files."
+            }
+        }
+    }
+}
+
+/// Problems with input images that are found before images are compared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputDiagnosis {
+    pub n_files: usize,
+    /// Number of images that were fully decoded; only a sample of images is decoded.
+    pub n_decoded: usize,
+    pub problems: Vec<(InputProblemKind, PathBuf)>,
+}
+
+impl InputDiagnosis {
+    pub fn count(&self, kind: InputProblemKind) -> usize {
+        self.problems.iter().filter(|(k, _)| *k == kind).count()
+    }
+
+    /// Number of files in which a kind of problem was looked for.
+    fn n_checked(&self, kind: InputProblemKind) -> usize {
+        if kind == InputProblemKind::CorruptPng {
+            self.n_decoded
+        } else {
+            self.n_files
+        }
+    }
+
+    /// Problems that affect so many files that a report would only repeat the same error:
+    /// any unreadable directory, or a kind of problem affecting at least half of the checked files.
+    pub fn systemic_problems(&self) -> Vec<InputProblemKind> {
+        [
+            InputProblemKind::UnreadableDirectory,
+            InputProblemKind::LfsPointer,
+            InputProblemKind::NotPng,
+            InputProblemKind::CorruptPng,
+        ]
+        .into_iter()
+        .filter(|kind| {
+            let count = self.count(*kind);
+            count > 0
+                && (*kind == InputProblemKind::UnreadableDirectory
+                    || count * 2 >= self.n_checked(*kind))
+        })
+        .collect()
+    }
+
+    pub fn is_systemic(&self) -> bool {
+        !self.systemic_problems().is_empty()
+    }
+
+    /// Print one diagnosis for each systemic problem.
+    pub fn print(&self) -> kompari::Result<()> {
+        let stderr = termcolor::StandardStream::stderr(termcolor::ColorChoice::Auto);
+        let mut stderr = stderr.lock();
+        for kind in self.systemic_problems() {
+            let paths: Vec<_> = self
+                .problems
+                .iter()
+                .filter(|(k, _)| *k == kind)
+                .map(|(_, path)| path)
+                .collect();
+            stderr.set_color(ColorSpec::new().set_fg(Some(Color::Red)))?;
+            write!(stderr, "error: ")?;
+            stderr.set_color(ColorSpec::new().set_fg(None))?;
+            if kind == InputProblemKind::UnreadableDirectory {
+                writeln!(stderr, "{} {}:", paths.len(), kind.describe())?;
+            } else {
+                writeln!(
+                    stderr,
+                    "{} of {} {}images are {}, e.g.:",
+                    paths.len(),
+                    self.n_checked(kind),
+                    if kind == InputProblemKind::CorruptPng {
+                        "decoded "
+                    } else {
+                        ""
+                    },
+                    kind.describe()
+                )?;
+            }
+            for path in paths.iter().take(N_EXAMPLES) {
+                writeln!(stderr, "    {}", path.display())?;
+            }
+            if paths.len() > N_EXAMPLES {
+                writeln!(stderr, "    ... and {} more", paths.len() - N_EXAMPLES)?;
+            }
+            stderr.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))?;
+            writeln!(stderr, "{}", kind.hint())?;
+            stderr.set_color(ColorSpec::new().set_fg(None))?;
+        }
+        Ok(())
+    }
+}
+
+fn check_directory(path: &Path, diagnosis: &mut InputDiagnosis) {
+    // Paths are empty when pairs are given by a manifest
+    if path.as_os_str().is_empty() {
+        return;
+    }
+    if !path.is_dir() {
+        diagnosis
+            .problems
+            .push((InputProblemKind::UnreadableDirectory, path.to_path_buf()));
+        return;
+    }
+    for entry in WalkDir::new(path) {
+        if let Err(e) = entry {
+            let path = e.path().unwrap_or(path).to_path_buf();
+            diagnosis
+                .problems
+                .push((InputProblemKind::UnreadableDirectory, path));
+        }
+    }
+}
+
+fn check_file(path: &Path, resolve_lfs: bool) -> Option<InputProblemKind> {
+    let mut header = [0; LFS_HEADER.len()];
+    let n = std::fs::File::open(path)
+        .and_then(|mut file| file.read(&mut header))
+        .ok();
+    match n {
+        Some(n) if header[..n].starts_with(PNG_SIGNATURE) => None,
+        Some(n) if header[..n] == *LFS_HEADER => {
+            if resolve_lfs && kompari::read_png_resolve_lfs(path).is_ok() {
+                None
+            } else {
+                Some(InputProblemKind::LfsPointer)
+            }
+        }
+        _ => Some(InputProblemKind::NotPng),
+    }
+}
+
+fn decode_file(path: &Path, resolve_lfs: bool) -> Option<InputProblemKind> {
+    let image = if resolve_lfs {
+        kompari::load_image_resolve_lfs(path)
+    } else {
+        kompari::load_image(path)
+    };
+    image.err().map(|_| InputProblemKind::CorruptPng)
+}
+
+/// Check input images without comparing them, to find problems that affect many files.
+///
+/// Only the beginning of each file is read, and a sample of at most 32 images
+/// is decoded, so this is much faster than creating the diff.
+pub fn diagnose_inputs(diff_config: &DirDiffConfig) -> kompari::Result<InputDiagnosis> {
+    let mut diagnosis = InputDiagnosis::default();
+    check_directory(diff_config.left_source_path(), &mut diagnosis);
+    check_directory(diff_config.right_path(), &mut diagnosis);
+    let pairs = match diff_config.pairs() {
+        Ok(pairs) => pairs,
+        // Already reported as an unreadable directory
+        Err(kompari::Error::NotDirectory(_)) => return Ok(diagnosis),
+        Err(e) => return Err(e),
+    };
+    let files: Vec<_> = pairs
+        .into_iter()
+        .flat_map(|pair| [pair.left, pair.right])
+        .filter(|path| path.is_file())
+        .collect();
+    diagnosis.n_files = files.len();
+    let resolve_lfs = diff_config.resolve_lfs();
+    let (problems, valid): (Vec<_>, Vec<_>) = files
+        .into_par_iter()
+        .map(|path| (check_file(&path, resolve_lfs), path))
+        .partition(|(problem, _)| problem.is_some());
+    // Evenly spaced sample of files with a valid header
+    let step = valid.len().div_ceil(N_DECODED).max(1);
+    let sample: Vec<_> = valid.into_iter().step_by(step).collect();
+    diagnosis.n_decoded = sample.len();
+    let decoded: Vec<_> = sample
+        .into_par_iter()
+        .map(|(_, path)| (decode_file(&path, resolve_lfs), path))
+        .collect();
+    let mut problems: Vec<_> = problems
+        .into_iter()
+        .chain(decoded)
+        .filter_map(|(kind, path)| Some((kind?, path)))
+        .collect();
+    problems.sort_unstable_by(|a, b| a.1.cmp(&b.1));
+    diagnosis.problems.extend(problems);
+    Ok(diagnosis)
+}
+
+/// Check inputs and print diagnoses of systemic problems.
+///
+/// If there is a systemic problem, the diagnosis is returned and images should not be compared;
+/// the caller is expected to exit with the code of [`CheckStatus::LoadErrors`](crate::CheckStatus::LoadErrors).
+pub fn report_systemic_problems(
+    diff_config: &DirDiffConfig,
+) -> kompari::Result<Option<InputDiagnosis>> {
+    let diagnosis = diagnose_inputs(diff_config)?;
+    if !diagnosis.is_systemic() {
+        return Ok(None);
+    }
+    diagnosis.print()?;
+    Ok(Some(diagnosis))
+}
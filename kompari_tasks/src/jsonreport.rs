@@ -0,0 +1,11 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use kompari::DirDiff;
+
+/// Render results of a diff as JSON.
+///
+/// Statistics, sizes, paths and errors are included; diff images are not.
+pub fn render_json_report(diff: &DirDiff) -> kompari::Result<String> {
+    serde_json::to_string_pretty(diff).map_err(|e| kompari::Error::GenericError(Box::new(e)))
+}
@@ -0,0 +1,163 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use kompari::{DirDiff, ImageDifference, PairResult, SizeOptimizationLevel, image_to_png};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+fn escape_xml(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// File name of a diff image; the index keeps names unique after replacing special characters.
+fn diff_image_name(idx: usize, title: &str, method: &str) -> String {
+    let title: String = title
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{idx:04}-{title}-{method}.png")
+}
+
+/// Returns `(element, message)` of a failed test case, or `None` if the images match.
+fn failure(result: &PairResult) -> Option<(&'static str, String)> {
+    match &result.image_diff {
+        Ok(ImageDifference::None) => None,
+        Ok(ImageDifference::SizeMismatch {
+            left_size,
+            right_size,
+        }) => Some((
+            "failure",
+            format!(
+                "Size mismatch: left {}x{}, right {}x{}",
+                left_size.0, left_size.1, right_size.0, right_size.1
+            ),
+        )),
+        Ok(ImageDifference::Content {
+            n_pixels,
+            n_different_pixels,
+            distance_sum,
+            ..
+        }) => {
+            let pct = *n_different_pixels as f32 / *n_pixels as f32 * 100.0;
+            let distance_sum = *distance_sum as f32 / 255.0;
+            Some((
+                "failure",
+                format!(
+                    "Different pixels: {n_different_pixels} of {n_pixels} ({pct:.1}%), \
+                     color distance: {distance_sum:.3}"
+                ),
+            ))
+        }
+        Err(e) if e.is_left_missing() || e.is_right_missing() => {
+            let side = match (e.is_left_missing(), e.is_right_missing()) {
+                (true, true) => "Both images are",
+                (true, false) => "Left image is",
+                _ => "Right image is",
+            };
+            Some(("failure", format!("{side} missing")))
+        }
+        Err(e) => {
+            let message = [e.left(), e.right()]
+                .into_iter()
+                .flatten()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("; ");
+            Some(("error", format!("Loading error: {message}")))
+        }
+    }
+}
+
+/// Write results of a diff as a `JUnit` XML report with one test case per pair.
+///
+/// Diff images are written into the directory `<report name>-images` next to the report
+/// and referenced from each test case, both as properties and as attachments
+/// (`[[ATTACHMENT|path]]` in the test case output).
+pub fn write_junit_report(diff: &DirDiff, path: &Path) -> kompari::Result<()> {
+    let image_dir = {
+        let mut name = path.file_stem().unwrap_or_default().to_os_string();
+        name.push("-images");
+        path.with_file_name(name)
+    };
+    let mut n_failures = 0;
+    let mut n_errors = 0;
+    let mut cases = String::new();
+    for (idx, result) in diff.results().iter().enumerate() {
+        let mut properties: Vec<(String, PathBuf)> = vec![
+            ("left".to_string(), result.left.clone()),
+            ("right".to_string(), result.right.clone()),
+        ];
+        if let Ok(ImageDifference::Content { diff_images, .. }) = &result.image_diff {
+            std::fs::create_dir_all(&image_dir)?;
+            for diff_image in diff_images {
+                let method = diff_image.method.to_string();
+                let image_path = image_dir.join(diff_image_name(idx, &result.title, &method));
+                std::fs::write(
+                    &image_path,
+                    image_to_png(&diff_image.image, SizeOptimizationLevel::None),
+                )?;
+                properties.push((format!("diff_image_{method}"), image_path));
+            }
+        }
+        let title = escape_xml(&result.title);
+        writeln!(
+            cases,
+            r#"    <testcase classname="kompari" name="{title}">"#
+        )
+        .unwrap();
+        cases.push_str("      <properties>\n");
+        for (name, value) in &properties {
+            let value = escape_xml(&value.display().to_string());
+            writeln!(
+                cases,
+                r#"        <property name="{name}" value="{value}"/>"#
+            )
+            .unwrap();
+        }
+        cases.push_str("      </properties>\n");
+        if let Some((element, message)) = failure(result) {
+            if element == "error" {
+                n_errors += 1;
+            } else {
+                n_failures += 1;
+            }
+            let message = escape_xml(&message);
+            writeln!(cases, r#"      <{element} message="{message}"/>"#).unwrap();
+        }
+        let attachments: Vec<_> = properties
+            .iter()
+            .filter(|(name, _)| name.starts_with("diff_image_"))
+            .map(|(_, path)| escape_xml(&format!("[[ATTACHMENT|{}]]", path.display())))
+            .collect();
+        if !attachments.is_empty() {
+            writeln!(
+                cases,
+                "      <system-out>{}</system-out>",
+                attachments.join("\n")
+            )
+            .unwrap();
+        }
+        cases.push_str("    </testcase>\n");
+    }
+    let n_tests = diff.results().len();
+    let report = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites>\n  \
+         <testsuite name=\"kompari\" tests=\"{n_tests}\" failures=\"{n_failures}\" errors=\"{n_errors}\">\n\
+         {cases}  </testsuite>\n\
+         </testsuites>\n"
+    );
+    std::fs::write(path, report)?;
+    Ok(())
+}
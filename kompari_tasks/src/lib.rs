@@ -13,9 +13,21 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 pub mod args;
+mod bless;
+mod check;
+mod diagnose;
+mod jsonreport;
+mod junit;
 mod optimizations;
+mod show;
 mod task;
 
 pub use args::Args;
+pub use bless::{require_left_working_tree, run_bless, run_undo, select_pairs_to_bless};
+pub use check::{CheckStatus, ERROR_EXIT_CODE, run_check};
+pub use diagnose::{InputDiagnosis, InputProblemKind, diagnose_inputs, report_systemic_problems};
+pub use jsonreport::render_json_report;
+pub use junit::write_junit_report;
 pub use optimizations::{OptimizationResult, check_size_optimizations};
-pub use task::{Actions, Task};
+pub use show::{detect_graphics_protocol, show_diff};
+pub use task::{Actions, Task, TaskOutcome};
@@ -0,0 +1,325 @@
+// Copyright 2025 the Kompari Authors
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::args::{GraphicsProtocol, ShowArgs};
+use base64::prelude::*;
+use kompari::color::Rgba8;
+use kompari::{
+    DirDiffConfig, ImageDifference, MinImage, PairResult, SizeOptimizationLevel, image_to_png,
+};
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+const GAP: u32 = 8;
+const PLACEHOLDER: Rgba8 = Rgba8 {
+    r: 128,
+    g: 128,
+    b: 128,
+    a: 255,
+};
+const TRANSPARENT: Rgba8 = Rgba8 {
+    r: 0,
+    g: 0,
+    b: 0,
+    a: 0,
+};
+
+/// Guess the inline graphics protocol supported by the terminal from environment variables.
+pub fn detect_graphics_protocol() -> GraphicsProtocol {
+    if !std::io::stdout().is_terminal() {
+        return GraphicsProtocol::None;
+    }
+    let var = |name| std::env::var(name).unwrap_or_default();
+    let term = var("TERM");
+    let program = var("TERM_PROGRAM");
+    if !var("KITTY_WINDOW_ID").is_empty() || term.contains("kitty") || program == "ghostty" {
+        GraphicsProtocol::Kitty
+    } else if program == "iTerm.app" || program == "WezTerm" || var("LC_TERMINAL") == "iTerm2" {
+        GraphicsProtocol::Iterm
+    } else if term.contains("sixel") || term == "foot" || term.starts_with("mlterm") {
+        GraphicsProtocol::Sixel
+    } else {
+        GraphicsProtocol::None
+    }
+}
+
+/// Nearest-neighbour downscaling; images are never enlarged.
+#[allow(
+    clippy::cast_possible_truncation,
+    reason = "Scaled coordinates are bounded by the image size"
+)]
+fn scale_to_height(image: &MinImage, height: u32) -> MinImage {
+    let height = height.min(image.height).max(1);
+    let width = ((image.width as u64 * height as u64) / image.height.max(1) as u64).max(1) as u32;
+    let mut data = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        let src_y = (y as u64 * image.height as u64 / height as u64) as u32;
+        for x in 0..width {
+            let src_x = (x as u64 * image.width as u64 / width as u64) as u32;
+            data.push(image.data[(src_y * image.width + src_x) as usize]);
+        }
+    }
+    MinImage {
+        width,
+        height,
+        data,
+    }
+}
+
+/// Put thumbnails of images next to each other; missing images are shown as gray squares.
+fn compose_strip(images: &[Option<&MinImage>], height: u32) -> MinImage {
+    let thumbnails: Vec<MinImage> = images
+        .iter()
+        .map(|image| match image {
+            Some(image) => scale_to_height(image, height),
+            None => MinImage {
+                width: height / 2,
+                height: height / 2,
+                data: vec![PLACEHOLDER; (height / 2 * height / 2) as usize],
+            },
+        })
+        .collect();
+    let strip_height = thumbnails.iter().map(|t| t.height).max().unwrap_or(1);
+    let strip_width = thumbnails.iter().map(|t| t.width + GAP).sum::<u32>() - GAP;
+    let mut data = vec![TRANSPARENT; (strip_width * strip_height) as usize];
+    let mut offset = 0;
+    for thumbnail in &thumbnails {
+        for y in 0..thumbnail.height {
+            let src = (y * thumbnail.width) as usize;
+            let dst = (y * strip_width + offset) as usize;
+            data[dst..dst + thumbnail.width as usize]
+                .copy_from_slice(&thumbnail.data[src..src + thumbnail.width as usize]);
+        }
+        offset += thumbnail.width + GAP;
+    }
+    MinImage {
+        width: strip_width,
+        height: strip_height,
+        data,
+    }
+}
+
+fn encode_kitty(image: &MinImage) -> String {
+    let data = BASE64_STANDARD.encode(image_to_png(image, SizeOptimizationLevel::None));
+    let chunks: Vec<_> = data.as_bytes().chunks(4096).collect();
+    let mut result = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).unwrap();
+        if i == 0 {
+            write!(result, "\x1b_Ga=T,f=100,m={more};{chunk}\x1b\\").unwrap();
+        } else {
+            write!(result, "\x1b_Gm={more};{chunk}\x1b\\").unwrap();
+        }
+    }
+    result
+}
+
+fn encode_iterm(image: &MinImage) -> String {
+    let data = image_to_png(image, SizeOptimizationLevel::None);
+    format!(
+        "\x1b]1337;File=inline=1;size={}:{}\x07",
+        data.len(),
+        BASE64_STANDARD.encode(&data)
+    )
+}
+
+fn push_sixel_run(result: &mut String, ch: u8, count: usize) {
+    if count > 3 {
+        write!(result, "!{count}{}", ch as char).unwrap();
+    } else {
+        for _ in 0..count {
+            result.push(ch as char);
+        }
+    }
+}
+
+/// Encode an image as sixels with a fixed 6x6x6 color cube; transparent pixels are not painted.
+fn encode_sixel(image: &MinImage) -> String {
+    let level = |c: u8| (c as u32 * 5 + 127) / 255;
+    let indices: Vec<Option<usize>> = image
+        .data
+        .iter()
+        .map(|c| (c.a >= 128).then(|| (level(c.r) * 36 + level(c.g) * 6 + level(c.b)) as usize))
+        .collect();
+    let mut result = format!("\x1bP0;1q\"1;1;{};{}", image.width, image.height);
+    for i in 0..216 {
+        write!(
+            result,
+            "#{i};2;{};{};{}",
+            i / 36 * 20,
+            i / 6 % 6 * 20,
+            i % 6 * 20
+        )
+        .unwrap();
+    }
+    let width = image.width as usize;
+    for band in (0..image.height as usize).step_by(6) {
+        let rows = band..(band + 6).min(image.height as usize);
+        let mut used = [false; 216];
+        for y in rows.clone() {
+            for idx in indices[y * width..(y + 1) * width].iter().flatten() {
+                used[*idx] = true;
+            }
+        }
+        for color in (0..216).filter(|c| used[*c]) {
+            write!(result, "#{color}").unwrap();
+            let mut run = (0, 0);
+            for x in 0..width {
+                let mut bits = 0;
+                for y in rows.clone() {
+                    if indices[y * width + x] == Some(color) {
+                        bits |= 1 << (y - band);
+                    }
+                }
+                let ch = 63 + bits;
+                if ch == run.0 {
+                    run.1 += 1;
+                } else {
+                    push_sixel_run(&mut result, run.0, run.1);
+                    run = (ch, 1);
+                }
+            }
+            push_sixel_run(&mut result, run.0, run.1);
+            result.push('$');
+        }
+        result.push('-');
+    }
+    result.push_str("\x1b\\");
+    result
+}
+
+fn load(path: &Path, resolve_lfs: bool) -> Option<MinImage> {
+    if resolve_lfs {
+        kompari::load_image_resolve_lfs(path).ok()
+    } else {
+        kompari::load_image(path).ok()
+    }
+}
+
+/// Print a status line of a pair, followed by thumbnails unless `protocol` is `None`.
+pub(crate) fn print_pair(
+    stdout: &mut impl WriteColor,
+    pair: &PairResult,
+    protocol: GraphicsProtocol,
+    thumbnail_height: u32,
+    resolve_lfs: bool,
+) -> kompari::Result<()> {
+    let (color, status, details) = match &pair.image_diff {
+        Ok(ImageDifference::None) => (Color::Green, "Match", String::new()),
+        Ok(ImageDifference::SizeMismatch {
+            left_size,
+            right_size,
+        }) => (
+            Color::Red,
+            "Size mismatch",
+            format!(
+                "{}x{} -> {}x{}",
+                left_size.0, left_size.1, right_size.0, right_size.1
+            ),
+        ),
+        Ok(ImageDifference::Content {
+            n_pixels,
+            n_different_pixels,
+            ..
+        }) => {
+            let pct = *n_different_pixels as f32 / *n_pixels as f32 * 100.0;
+            (
+                Color::Yellow,
+                "Different",
+                format!("{n_different_pixels} pixels ({pct:.1}%)"),
+            )
+        }
+        Err(e) if e.is_left_missing() || e.is_right_missing() => (
+            Color::Red,
+            "Missing",
+            match (e.is_left_missing(), e.is_right_missing()) {
+                (true, true) => "both images".to_string(),
+                (true, false) => "left image".to_string(),
+                _ => "right image".to_string(),
+            },
+        ),
+        Err(e) => (
+            Color::Red,
+            "Loading error",
+            [e.left(), e.right()]
+                .into_iter()
+                .flatten()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("; "),
+        ),
+    };
+    stdout.set_color(ColorSpec::new().set_fg(None))?;
+    write!(stdout, "{}: ", pair.title)?;
+    stdout.set_color(ColorSpec::new().set_fg(Some(color)))?;
+    write!(stdout, "{status} ")?;
+    stdout.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+    writeln!(stdout, "{details}")?;
+    stdout.set_color(ColorSpec::new().set_fg(None))?;
+
+    if protocol == GraphicsProtocol::None {
+        return Ok(());
+    }
+    let left = load(&pair.left, resolve_lfs);
+    let right = load(&pair.right, resolve_lfs);
+    let diff_image = match &pair.image_diff {
+        Ok(ImageDifference::Content { diff_images, .. }) => diff_images.first(),
+        _ => None,
+    };
+    let mut images = vec![left.as_ref(), right.as_ref()];
+    if let Some(diff_image) = diff_image {
+        images.push(Some(&diff_image.image));
+    }
+    let strip = compose_strip(&images, thumbnail_height);
+    let encoded = match protocol {
+        GraphicsProtocol::Kitty => encode_kitty(&strip),
+        GraphicsProtocol::Iterm => encode_iterm(&strip),
+        GraphicsProtocol::Sixel => encode_sixel(&strip),
+        GraphicsProtocol::Auto | GraphicsProtocol::None => unreachable!(),
+    };
+    stdout.write_all(encoded.as_bytes())?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+/// Print a summary of differences into the terminal.
+///
+/// Thumbnails of the left image, the right image and the difference are shown
+/// if the terminal supports an inline graphics protocol.
+pub fn show_diff(diff_config: &DirDiffConfig, args: &ShowArgs) -> kompari::Result<()> {
+    let protocol = match args.graphics {
+        GraphicsProtocol::Auto => detect_graphics_protocol(),
+        protocol => protocol,
+    };
+    let diff = diff_config.create_diff()?;
+    let stdout = termcolor::StandardStream::stdout(termcolor::ColorChoice::Auto);
+    let mut stdout = stdout.lock();
+    for pair in diff.results() {
+        print_pair(
+            &mut stdout,
+            pair,
+            protocol,
+            args.thumbnail_height,
+            diff_config.resolve_lfs(),
+        )?;
+    }
+    let n_different = diff
+        .results()
+        .iter()
+        .filter(|r| !matches!(r.image_diff, Ok(ImageDifference::None)))
+        .count();
+    if n_different == 0 {
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::Green)))?;
+        writeln!(stdout, "All images match")?;
+    } else {
+        writeln!(
+            stdout,
+            "----------------------------\n{n_different} image(s) differ"
+        )?;
+    }
+    stdout.set_color(ColorSpec::new().set_fg(None))?;
+    Ok(())
+}
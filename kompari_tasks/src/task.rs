@@ -1,10 +1,16 @@
 // Copyright 2024 the Kompari Authors
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use crate::args::{Args, Command, DeadSnapshotArgs};
-use crate::check_size_optimizations;
+use crate::args::{Args, Command, DeadSnapshotArgs, ReportFormat};
+use crate::{
+    CheckStatus, InputDiagnosis, check_size_optimizations, render_json_report,
+    report_systemic_problems, require_left_working_tree, run_bless, run_check, run_undo, show_diff,
+    write_junit_report,
+};
 use kompari::{DirDiffConfig, list_image_dir, list_image_dir_names};
-use kompari_html::{ReportConfig, render_html_report, start_review_server};
+use kompari_html::{
+    ReportConfig, ReviewSummary, render_html_report, render_markdown_report, start_review_server,
+};
 use std::collections::BTreeSet;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
@@ -13,6 +19,34 @@ pub trait Actions: std::fmt::Debug {
     fn generate_all_tests(&self) -> kompari::Result<()>;
 }
 
+/// Outcome of [`Task::run`]; the xtask decides how to exit, e.g. with [`Self::exit_code`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskOutcome {
+    /// The command finished.
+    Done,
+    /// Status of the `check` command, or of `report` with `--exit-code`.
+    Check(CheckStatus),
+    /// Summary of the review; cases left unresolved are reported as differences.
+    Review(ReviewSummary),
+    /// Images were not compared because of systemic problems of inputs, which were printed.
+    InputProblems(InputDiagnosis),
+}
+
+impl TaskOutcome {
+    /// Process exit code, see [`CheckStatus::exit_code`].
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Done => 0,
+            Self::Check(status) => status.exit_code(),
+            Self::Review(summary) if summary.n_unresolved() > 0 => {
+                CheckStatus::Differences.exit_code()
+            }
+            Self::Review(_) => 0,
+            Self::InputProblems(_) => CheckStatus::LoadErrors.exit_code(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Task {
     diff_config: DirDiffConfig,
@@ -42,27 +76,55 @@ impl Task {
         self.report_output_path = path;
     }
 
-    pub fn run(&mut self, args: &Args) -> kompari::Result<()> {
+    /// Run the command; errors should be reported with [`ERROR_EXIT_CODE`](crate::ERROR_EXIT_CODE).
+    pub fn run(&mut self, args: &Args) -> kompari::Result<TaskOutcome> {
         match &args.command {
             Command::Report(report_args) => {
-                if report_args.embed_images {
+                // Images exported from a git revision are removed when the process exits
+                if report_args.embed_images || self.diff_config.left_revision().is_some() {
                     self.report_config.set_embed_images(true);
                 }
                 self.report_config
                     .set_size_optimization(report_args.optimize_size.to_level());
-                let output: &Path = report_args
-                    .output
-                    .as_deref()
-                    .unwrap_or(self.report_output_path.as_path());
+                let default_output = self
+                    .report_output_path
+                    .with_extension(report_args.format.extension());
+                let output: &Path = report_args.output.as_deref().unwrap_or(&default_output);
+                if let Some(diagnosis) = report_systemic_problems(&self.diff_config)? {
+                    return Ok(TaskOutcome::InputProblems(diagnosis));
+                }
                 let diff = self.diff_config.create_diff()?;
-                let report = render_html_report(&self.report_config, diff.results())?;
+                let report = match report_args.format {
+                    ReportFormat::Html => render_html_report(&self.report_config, diff.results())?,
+                    ReportFormat::Json => render_json_report(&diff)?,
+                    ReportFormat::Markdown => render_markdown_report(
+                        &self.report_config,
+                        diff.results(),
+                        report_args.markdown_images.then_some(output),
+                    )?,
+                };
                 std::fs::write(output, report)?;
                 println!("Report written into '{}'", output.display());
+                if let Some(junit) = &report_args.junit {
+                    write_junit_report(&diff, junit)?;
+                    println!("JUnit report written into '{}'", junit.display());
+                }
+                if report_args.exit_code {
+                    return Ok(TaskOutcome::Check(CheckStatus::of_results(diff.results())));
+                }
             }
             Command::Review(args) => {
                 self.report_config
                     .set_size_optimization(args.optimize_size.to_level());
-                start_review_server(&self.diff_config, &self.report_config, args.port)?;
+                if let Some(diagnosis) = report_systemic_problems(&self.diff_config)? {
+                    return Ok(TaskOutcome::InputProblems(diagnosis));
+                }
+                let summary = start_review_server(
+                    &self.diff_config,
+                    &self.report_config,
+                    &args.review_config(),
+                )?;
+                return Ok(TaskOutcome::Review(summary));
             }
             Command::Clean => {
                 clean_image_dir(self.diff_config.right_path())?;
@@ -78,8 +140,28 @@ impl Task {
             Command::SizeCheck(sc_args) => {
                 check_size_optimizations(self.diff_config.left_path(), sc_args)?;
             }
+            Command::Check(check_args) => {
+                if let Some(diagnosis) = report_systemic_problems(&self.diff_config)? {
+                    return Ok(TaskOutcome::InputProblems(diagnosis));
+                }
+                let status = run_check(&self.diff_config, &self.report_config, check_args)?;
+                return Ok(TaskOutcome::Check(status));
+            }
+            Command::Bless(bless_args) => {
+                run_bless(&self.diff_config, bless_args)?;
+            }
+            Command::Undo => {
+                require_left_working_tree(&self.diff_config)?;
+                run_undo(&self.diff_config.journal()?)?;
+            }
+            Command::Show(show_args) => {
+                if let Some(diagnosis) = report_systemic_problems(&self.diff_config)? {
+                    return Ok(TaskOutcome::InputProblems(diagnosis));
+                }
+                show_diff(&self.diff_config, show_args)?;
+            }
         }
-        Ok(())
+        Ok(TaskOutcome::Done)
     }
 }
 